@@ -5,12 +5,20 @@
 //! topology constructors ([`Backend::linear`], [`Backend::grid`], etc.).
 
 use crate::error::{QRustError, Result};
+use crate::ir::{Circuit, GateType, Operation};
+use petgraph::algo::dijkstra;
 use petgraph::graph::{Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
 use petgraph::Directed;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::path::Path;
 
+/// Routing cost `-ln(1 - error)` of an edge with the given error rate.
+fn edge_cost(error: f64) -> f64 {
+    -(1.0 - error).ln()
+}
+
 /// JSON-deserializable backend description.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackendConfig {
@@ -22,6 +30,28 @@ pub struct BackendConfig {
     pub basis_gates: Vec<String>,
     /// Directed coupling map as `[from, to]` pairs.
     pub coupling_map: Vec<[usize; 2]>,
+    /// Two-qubit error rates as `[u, v, error]` entries, one per undirected
+    /// edge (see [`Backend::set_edge_error`]). Optional in the JSON.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edge_errors: Vec<(usize, usize, f64)>,
+    /// Gate durations keyed by gate name (see [`Backend::gate_durations`]).
+    /// Optional in the JSON.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub gate_durations: BTreeMap<String, u64>,
+    /// Duration of gates missing from `gate_durations`; `1` when omitted.
+    #[serde(default = "default_gate_duration")]
+    pub default_gate_duration: u64,
+    /// See [`Backend::supports_mid_circuit_measure`]; `true` when omitted.
+    #[serde(default = "default_true")]
+    pub supports_mid_circuit_measure: bool,
+}
+
+fn default_gate_duration() -> u64 {
+    1
+}
+
+fn default_true() -> bool {
+    true
 }
 
 /// Hardware backend: qubit count, native gate set, and coupling graph.
@@ -36,6 +66,26 @@ pub struct Backend {
     /// Directed coupling map: an edge `(u, v)` means a 2-qubit gate with
     /// control `u` and target `v` is natively supported.
     pub coupling_map: Graph<(), (), Directed>,
+    /// Optional two-qubit error rates keyed by `(min, max)` coupling edge.
+    /// A rate applies to both directions of its edge. When non-empty,
+    /// routing minimizes cumulative error instead of hop count (see
+    /// [`Backend::edge_weight`]). Private so that `mean_edge_cost` stays in
+    /// sync; use [`Backend::set_edge_error`] / [`Backend::edge_error`].
+    edge_errors: HashMap<(usize, usize), f64>,
+    /// Mean routing cost of the recorded edges, charged to edges without a
+    /// rate.
+    mean_edge_cost: f64,
+    /// Gate durations keyed by lower-case gate name (plus `measure` and
+    /// `reset`), in backend time units. See [`Backend::circuit_duration`].
+    pub gate_durations: HashMap<String, u64>,
+    /// Duration assumed for operations missing from `gate_durations`.
+    /// Defaults to `1`, so with no timing data the duration is the depth.
+    pub default_gate_duration: u64,
+    /// Whether gates may act on a qubit after it has been measured (without
+    /// an intervening reset). `true` by default; static backends that only
+    /// allow terminal measurement should clear it. See
+    /// [`Backend::validate_circuit`].
+    pub supports_mid_circuit_measure: bool,
 }
 
 impl Backend {
@@ -51,6 +101,11 @@ impl Backend {
             num_qubits,
             basis_gates: HashSet::new(),
             coupling_map: graph,
+            edge_errors: HashMap::new(),
+            mean_edge_cost: 1.0,
+            gate_durations: HashMap::new(),
+            default_gate_duration: 1,
+            supports_mid_circuit_measure: true,
         }
     }
 
@@ -60,7 +115,8 @@ impl Backend {
     }
 
     /// Replaces the coupling map with the given directed edges.
-    /// Self-loops and out-of-range edges are silently dropped.
+    /// Self-loops and out-of-range edges are silently dropped, as are the
+    /// error rates of edges that are no longer coupled.
     pub fn set_coupling_map(&mut self, edges: impl IntoIterator<Item = (usize, usize)>) {
         self.coupling_map.clear_edges();
         for (u, v) in edges {
@@ -69,15 +125,95 @@ impl Backend {
                     .add_edge(NodeIndex::new(u), NodeIndex::new(v), ());
             }
         }
+        let mut errors = std::mem::take(&mut self.edge_errors);
+        errors.retain(|&(u, v), _| self.is_adjacent(u, v));
+        self.edge_errors = errors;
+        self.update_mean_edge_cost();
+    }
+
+    /// Strict variant of [`Backend::set_coupling_map`].
+    ///
+    /// # Errors
+    /// Returns [`QRustError::InvalidConfig`] naming the first self-loop or
+    /// out-of-range edge. The existing coupling map is left untouched on
+    /// error.
+    pub fn try_set_coupling_map(
+        &mut self,
+        edges: impl IntoIterator<Item = (usize, usize)>,
+    ) -> Result<()> {
+        let edges: Vec<(usize, usize)> = edges.into_iter().collect();
+        for &(u, v) in &edges {
+            if u == v {
+                return Err(QRustError::InvalidConfig(format!(
+                    "coupling map edge ({u}, {v}) is a self-loop"
+                )));
+            }
+            if u >= self.num_qubits || v >= self.num_qubits {
+                return Err(QRustError::InvalidConfig(format!(
+                    "coupling map edge ({u}, {v}) is out of range for backend '{}' with {} qubits",
+                    self.name, self.num_qubits
+                )));
+            }
+        }
+        self.set_coupling_map(edges);
+        Ok(())
     }
 
-    /// Constructs a backend from a JSON [`BackendConfig`].
+    /// Records the two-qubit error rate of the edge between `u` and `v`.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::InvalidConfig`] if the qubits are not coupled
+    /// or `error` is outside `[0, 1)`.
+    pub fn set_edge_error(&mut self, u: usize, v: usize, error: f64) -> Result<()> {
+        if !(0.0..1.0).contains(&error) {
+            return Err(QRustError::InvalidConfig(format!(
+                "edge error {error} for ({u}, {v}) must lie in [0, 1)"
+            )));
+        }
+        if u >= self.num_qubits || v >= self.num_qubits || !self.is_adjacent(u, v) {
+            return Err(QRustError::InvalidConfig(format!(
+                "edge ({u}, {v}) is not in the coupling map of backend '{}'",
+                self.name
+            )));
+        }
+        self.edge_errors.insert((u.min(v), u.max(v)), error);
+        self.update_mean_edge_cost();
+        Ok(())
+    }
+
+    /// Recomputes the cached mean edge cost used by [`Backend::edge_weight`].
+    fn update_mean_edge_cost(&mut self) {
+        self.mean_edge_cost = if self.edge_errors.is_empty() {
+            1.0
+        } else {
+            self.edge_errors
+                .values()
+                .map(|&e| edge_cost(e))
+                .sum::<f64>()
+                / self.edge_errors.len() as f64
+        };
+    }
+
+    /// Returns the recorded error rate of the edge between `u` and `v`.
+    pub fn edge_error(&self, u: usize, v: usize) -> Option<f64> {
+        self.edge_errors.get(&(u.min(v), u.max(v))).copied()
+    }
+
+    /// Constructs a backend from a JSON [`BackendConfig`]. Like invalid
+    /// coupling edges, error rates that [`Backend::set_edge_error`] would
+    /// reject are dropped.
     pub fn from_config(config: BackendConfig) -> Self {
         let mut backend = Backend::new(config.backend_name, config.n_qubits);
         for gate in config.basis_gates {
             backend.add_basis_gate(gate);
         }
         backend.set_coupling_map(config.coupling_map.into_iter().map(|arr| (arr[0], arr[1])));
+        for (u, v, error) in config.edge_errors {
+            let _ = backend.set_edge_error(u, v, error);
+        }
+        backend.gate_durations = config.gate_durations.into_iter().collect();
+        backend.default_gate_duration = config.default_gate_duration;
+        backend.supports_mid_circuit_measure = config.supports_mid_circuit_measure;
         backend
     }
 
@@ -91,11 +227,60 @@ impl Backend {
         let path = path.as_ref();
         let text = std::fs::read_to_string(path)
             .map_err(|e| QRustError::ParseError(format!("cannot read {}: {e}", path.display())))?;
-        let cfg: BackendConfig = serde_json::from_str(&text)
+        Backend::from_json(&text)
+    }
+
+    /// Parses a backend from a JSON [`BackendConfig`] string.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::ParseError`] on invalid JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let cfg: BackendConfig = serde_json::from_str(json)
             .map_err(|e| QRustError::ParseError(format!("invalid backend JSON: {e}")))?;
         Ok(Backend::from_config(cfg))
     }
 
+    /// Converts the backend back into a [`BackendConfig`]. Basis gates,
+    /// edge errors and gate durations are sorted and the coupling map is
+    /// listed in edge-insertion order, so the result is deterministic and
+    /// round-trips through [`Backend::from_config`].
+    pub fn to_config(&self) -> BackendConfig {
+        let mut basis_gates: Vec<String> = self.basis_gates.iter().cloned().collect();
+        basis_gates.sort();
+        let coupling_map = self
+            .coupling_map
+            .edge_indices()
+            .filter_map(|e| self.coupling_map.edge_endpoints(e))
+            .map(|(u, v)| [u.index(), v.index()])
+            .collect();
+        let mut edge_errors: Vec<(usize, usize, f64)> = self
+            .edge_errors
+            .iter()
+            .map(|(&(u, v), &e)| (u, v, e))
+            .collect();
+        edge_errors.sort_by_key(|&(u, v, _)| (u, v));
+        BackendConfig {
+            backend_name: self.name.clone(),
+            n_qubits: self.num_qubits,
+            basis_gates,
+            coupling_map,
+            edge_errors,
+            gate_durations: self
+                .gate_durations
+                .iter()
+                .map(|(name, &d)| (name.clone(), d))
+                .collect(),
+            default_gate_duration: self.default_gate_duration,
+            supports_mid_circuit_measure: self.supports_mid_circuit_measure,
+        }
+    }
+
+    /// Serializes the backend as a JSON [`BackendConfig`].
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(&self.to_config())
+            .map_err(|e| QRustError::Internal(format!("cannot serialize backend: {e}")))
+    }
+
     /// Constructs IBM Quito's 5-qubit T-shaped heavy-hex topology.
     ///
     /// Coupling: 0—1—2, with 1—3 and 3—4 forming the T:
@@ -231,6 +416,51 @@ impl Backend {
         backend
     }
 
+    /// Builds a random connected topology with bidirectional edges, for
+    /// property-testing layout and routing.
+    ///
+    /// A random spanning tree guarantees connectivity; every remaining pair
+    /// of qubits is then coupled with probability `connectivity` (clamped to
+    /// `[0, 1]`), so `0.0` yields a tree and `1.0` an all-to-all graph. The
+    /// same `seed` always produces the same backend.
+    pub fn random(num_qubits: usize, connectivity: f64, seed: u64) -> Self {
+        let mut backend = Backend::new(format!("random_{num_qubits}"), num_qubits);
+        let p = connectivity.clamp(0.0, 1.0);
+        let mut state = seed;
+        let mut next = move || {
+            // splitmix64
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+
+        let mut order: Vec<usize> = (0..num_qubits).collect();
+        for i in (1..num_qubits).rev() {
+            order.swap(i, (next() % (i as u64 + 1)) as usize);
+        }
+        let mut adjacent = HashSet::new();
+        for i in 1..num_qubits {
+            let parent = order[(next() % i as u64) as usize];
+            let child = order[i];
+            adjacent.insert((parent.min(child), parent.max(child)));
+        }
+        for i in 0..num_qubits {
+            for j in i + 1..num_qubits {
+                let coin = (next() >> 11) as f64 / (1u64 << 53) as f64;
+                if coin < p {
+                    adjacent.insert((i, j));
+                }
+            }
+        }
+
+        let mut edges: Vec<(usize, usize)> = adjacent.into_iter().collect();
+        edges.sort_unstable();
+        backend.set_coupling_map(edges.into_iter().flat_map(|(u, v)| [(u, v), (v, u)]));
+        backend
+    }
+
     /// Returns `true` iff a 2-qubit gate can run between `q1` and `q2` in
     /// either direction.
     #[inline]
@@ -278,6 +508,190 @@ impl Backend {
         true
     }
 
+    /// Returns `true` iff `gate` can run on `qubits` as written: every qubit
+    /// exists, the gate is in the native basis (an empty basis allows any
+    /// gate; `u` also matches `u3`/`U`), and the operands are coupled.
+    ///
+    /// Two-qubit gates need the directed edge `qubits[0] -> qubits[1]`,
+    /// except symmetric ones (`cz`, `swap`, `rxx`, `ryy`, `rzz`, `iswap`),
+    /// which accept either direction. Wider gates need every pair of
+    /// operands adjacent. Barriers only need their qubits to exist.
+    pub fn allows_gate_on(&self, gate: &GateType, qubits: &[usize]) -> bool {
+        if qubits.iter().any(|&q| q >= self.num_qubits) {
+            return false;
+        }
+        if *gate == GateType::Barrier {
+            return true;
+        }
+        let name = gate.to_qasm_name();
+        let in_basis = self.basis_gates.is_empty()
+            || self.basis_gates.contains(name)
+            || (*gate == GateType::U
+                && (self.basis_gates.contains("u3") || self.basis_gates.contains("U")));
+        if !in_basis {
+            return false;
+        }
+        match qubits {
+            [] | [_] => true,
+            &[a, b] => {
+                let symmetric = matches!(
+                    gate,
+                    GateType::CZ
+                        | GateType::SWAP
+                        | GateType::RXX
+                        | GateType::RYY
+                        | GateType::RZZ
+                        | GateType::ISwap
+                );
+                if symmetric {
+                    self.is_adjacent(a, b)
+                } else {
+                    self.has_directed_edge(a, b)
+                }
+            }
+            _ => qubits
+                .iter()
+                .enumerate()
+                .all(|(i, &a)| qubits[i + 1..].iter().all(|&b| self.is_adjacent(a, b))),
+        }
+    }
+
+    /// Checks that `circuit` can run on this backend as written.
+    ///
+    /// # Errors
+    /// - [`QRustError::InsufficientQubits`] if the circuit is wider than the
+    ///   backend.
+    /// - [`QRustError::Unsupported`] if the backend lacks mid-circuit
+    ///   measurement and a gate acts on a qubit that was measured and not
+    ///   reset since.
+    pub fn validate_circuit(&self, circuit: &Circuit) -> Result<()> {
+        if circuit.num_qubits > self.num_qubits {
+            return Err(QRustError::InsufficientQubits {
+                circuit: circuit.num_qubits,
+                backend: self.num_qubits,
+            });
+        }
+        if self.supports_mid_circuit_measure {
+            return Ok(());
+        }
+        let mut measured: HashMap<usize, usize> = HashMap::new();
+        for (i, op) in circuit.operations.iter().enumerate() {
+            match op {
+                Operation::Measure { qubit, .. } => {
+                    measured.entry(*qubit).or_insert(i);
+                }
+                Operation::Reset { qubit } => {
+                    measured.remove(qubit);
+                }
+                Operation::Barrier { .. } => {}
+                other => {
+                    if let Some((q, at)) = other
+                        .qubits()
+                        .iter()
+                        .find_map(|q| measured.get(q).map(|&at| (*q, at)))
+                    {
+                        return Err(QRustError::Unsupported(format!(
+                            "operation #{i} ({}) acts on qubit {q} after its measurement \
+                             (op #{at}), but backend '{}' does not support mid-circuit \
+                             measurement",
+                            other.to_string().trim_end_matches(';'),
+                            self.name
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the duration of a single operation: conditionals take their
+    /// inner operation's time and barriers take none.
+    pub fn op_duration(&self, op: &Operation) -> u64 {
+        match op.duration_key() {
+            Some(key) => self
+                .gate_durations
+                .get(key)
+                .copied()
+                .unwrap_or(self.default_gate_duration),
+            None => 0,
+        }
+    }
+
+    /// Total wall-time of `circuit` under an as-soon-as-possible schedule
+    /// using [`Backend::op_duration`], i.e. its duration-weighted critical
+    /// path (see [`Circuit::weighted_depth`]).
+    pub fn circuit_duration(&self, circuit: &Circuit) -> u64 {
+        circuit.weighted_depth(|op| self.op_duration(op))
+    }
+
+    /// Estimated hardware cost of running `circuit` on this backend, for
+    /// ranking equivalent transpilation outputs (lower is better).
+    ///
+    /// Each multi-qubit gate contributes its CX-equivalent count (SWAP = 3,
+    /// CCX = 6, controlled rotations and two-qubit rotations = 2, other
+    /// two-qubit gates = 1) times [`Backend::edge_weight`] of its first two
+    /// qubits. Without error data the result is the CX-equivalent count;
+    /// with it, the result approximates `-ln(success probability)` of the
+    /// two-qubit layer. Single-qubit gates, measurements and resets are
+    /// treated as free.
+    pub fn circuit_cost(&self, circuit: &Circuit) -> f64 {
+        fn cost(op: &Operation, backend: &Backend) -> f64 {
+            match op {
+                Operation::Gate { name, qubits, .. } if qubits.len() >= 2 => {
+                    let cx = match name {
+                        GateType::SWAP => 3.0,
+                        GateType::CCX => 6.0,
+                        GateType::CRX
+                        | GateType::CRY
+                        | GateType::CRZ
+                        | GateType::RXX
+                        | GateType::RYY
+                        | GateType::RZZ
+                        | GateType::ISwap => 2.0,
+                        _ => 1.0,
+                    };
+                    cx * backend.edge_weight(qubits[0], qubits[1])
+                }
+                Operation::Conditional { op, .. } => cost(op, backend),
+                _ => 0.0,
+            }
+        }
+        circuit.operations.iter().map(|op| cost(op, self)).sum()
+    }
+
+    /// Routing cost of one hop across the edge `u -> v`.
+    ///
+    /// Without error data every edge costs `1.0`, so costs are hop counts.
+    /// Otherwise an edge costs `-ln(1 - error)`, making path costs additive
+    /// in log-fidelity; edges with no recorded rate use the mean cost of the
+    /// recorded ones.
+    pub fn edge_weight(&self, u: usize, v: usize) -> f64 {
+        if self.edge_errors.is_empty() {
+            return 1.0;
+        }
+        self.edge_error(u, v).map_or(self.mean_edge_cost, edge_cost)
+    }
+
+    /// Returns an `n × n` matrix of least-cost path lengths between every
+    /// pair of qubits under [`Backend::edge_weight`], computed via Dijkstra.
+    /// `f64::INFINITY` indicates no path. Equals
+    /// [`Backend::shortest_path_matrix`] when no edge errors are recorded.
+    pub fn weighted_distance_matrix(&self) -> Vec<Vec<f64>> {
+        let n = self.num_qubits;
+        (0..n)
+            .map(|start| {
+                let costs = dijkstra(&self.coupling_map, NodeIndex::new(start), None, |e| {
+                    self.edge_weight(e.source().index(), e.target().index())
+                });
+                let mut row = vec![f64::INFINITY; n];
+                for (node, c) in costs {
+                    row[node.index()] = c;
+                }
+                row
+            })
+            .collect()
+    }
+
     /// Returns an `n × n` matrix of shortest-path distances between every
     /// pair of qubits, computed via BFS. `usize::MAX` indicates no path.
     pub fn shortest_path_matrix(&self) -> Vec<Vec<usize>> {
@@ -322,6 +736,28 @@ mod tests {
         assert!(!backend.is_adjacent(0, 2));
     }
 
+    #[test]
+    fn test_try_set_coupling_map_rejects_out_of_range_edge() {
+        let mut backend = Backend::new("test_backend", 3);
+        backend.set_coupling_map([(0, 1)]);
+        let err = backend.try_set_coupling_map([(1, 2), (2, 3)]).unwrap_err();
+        assert!(matches!(err, QRustError::InvalidConfig(_)));
+        assert!(err.to_string().contains("(2, 3)"), "{err}");
+        // The previous map survives a failed update.
+        assert!(backend.has_directed_edge(0, 1));
+        assert!(!backend.has_directed_edge(1, 2));
+    }
+
+    #[test]
+    fn test_try_set_coupling_map_rejects_self_loop() {
+        let mut backend = Backend::new("test_backend", 3);
+        let err = backend.try_set_coupling_map([(0, 1), (1, 1)]).unwrap_err();
+        assert!(matches!(err, QRustError::InvalidConfig(_)));
+        assert!(err.to_string().contains("(1, 1)"), "{err}");
+        assert!(backend.try_set_coupling_map([(0, 1), (1, 2)]).is_ok());
+        assert_eq!(backend.coupling_map.edge_count(), 2);
+    }
+
     #[test]
     fn test_has_directed_edge() {
         let mut b = Backend::new("t", 3);
@@ -376,6 +812,30 @@ mod tests {
         assert!(!backend.is_adjacent(0, 4));
     }
 
+    #[test]
+    fn test_allows_gate_on() {
+        let mut backend = Backend::new("dir", 3);
+        backend.set_coupling_map([(0, 1), (1, 2)]);
+        backend.add_basis_gate("cx");
+        backend.add_basis_gate("cz");
+        backend.add_basis_gate("u3");
+
+        assert!(backend.allows_gate_on(&GateType::CX, &[0, 1]));
+        assert!(!backend.allows_gate_on(&GateType::CX, &[1, 0]));
+        assert!(!backend.allows_gate_on(&GateType::CX, &[0, 2]));
+        assert!(backend.allows_gate_on(&GateType::CZ, &[1, 0]));
+        assert!(!backend.allows_gate_on(&GateType::CY, &[0, 1]));
+        assert!(backend.allows_gate_on(&GateType::U, &[2]));
+        assert!(!backend.allows_gate_on(&GateType::U, &[3]));
+        assert!(!backend.allows_gate_on(&GateType::H, &[0]));
+
+        let open = Backend::linear(3);
+        assert!(open.allows_gate_on(&GateType::H, &[0]));
+        assert!(open.allows_gate_on(&GateType::CX, &[2, 1]));
+        assert!(!open.allows_gate_on(&GateType::CCX, &[0, 1, 2]));
+        assert!(Backend::all_to_all(3).allows_gate_on(&GateType::CCX, &[0, 1, 2]));
+    }
+
     #[test]
     fn test_neighbors() {
         let backend = Backend::linear(5);
@@ -398,6 +858,112 @@ mod tests {
         assert_eq!(gd[0][4], 2);
     }
 
+    #[test]
+    fn test_random_backend_is_reproducible_and_connected() {
+        for seed in 0..8 {
+            let b = Backend::random(9, 0.3, seed);
+            let again = Backend::random(9, 0.3, seed);
+            assert_eq!(b.to_json().unwrap(), again.to_json().unwrap());
+
+            let dist = b.shortest_path_matrix();
+            for (i, row) in dist.iter().enumerate() {
+                for (j, &d) in row.iter().enumerate() {
+                    assert!(i == j || d != usize::MAX, "seed {seed}: {i} -/- {j}");
+                }
+            }
+        }
+        // Zero density leaves just the spanning tree.
+        assert_eq!(Backend::random(6, 0.0, 1).coupling_map.edge_count(), 2 * 5);
+        assert!(Backend::random(5, 1.0, 1).is_fully_connected());
+        assert_ne!(
+            Backend::random(9, 0.3, 1).to_json().unwrap(),
+            Backend::random(9, 0.3, 2).to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_validate_circuit_flags_gate_after_measure() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[2];\ncreg c[2];\nmeasure q[0] -> c[0];\nx q[0];\n",
+        )
+        .unwrap();
+        let mut backend = Backend::linear(2);
+        assert!(backend.validate_circuit(&c).is_ok());
+
+        backend.supports_mid_circuit_measure = false;
+        let err = backend.validate_circuit(&c).unwrap_err();
+        assert!(matches!(err, QRustError::Unsupported(_)));
+        assert!(err.to_string().contains("qubit 0"), "{err}");
+
+        // A reset in between makes the qubit usable again.
+        let reset = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[1];\ncreg c[1];\nmeasure q[0] -> c[0];\nreset q[0];\nx q[0];\n",
+        )
+        .unwrap();
+        assert!(backend.validate_circuit(&reset).is_ok());
+        assert!(matches!(
+            Backend::linear(1).validate_circuit(&c),
+            Err(QRustError::InsufficientQubits { .. })
+        ));
+    }
+
+    #[test]
+    fn test_circuit_duration_follows_critical_path() {
+        use crate::ir::GateType;
+        let mut backend = Backend::linear(3);
+        backend.gate_durations.insert("h".into(), 35);
+        backend.gate_durations.insert("cx".into(), 300);
+        backend.gate_durations.insert("measure".into(), 1000);
+        backend.default_gate_duration = 50;
+
+        let mut c = Circuit::new(3, 1);
+        let gate = |name, qubits: Vec<usize>| Operation::Gate {
+            name,
+            qubits,
+            params: vec![],
+        };
+        c.add_op(gate(GateType::H, vec![0]));
+        c.add_op(gate(GateType::CX, vec![0, 1]));
+        // Runs in parallel with the first two gates.
+        c.add_op(gate(GateType::X, vec![2]));
+        c.add_op(Operation::Barrier { qubits: vec![1, 2] });
+        c.add_op(gate(GateType::CX, vec![1, 2]));
+        c.add_op(Operation::Measure { qubit: 2, cbit: 0 });
+        assert_eq!(backend.circuit_duration(&c), 35 + 300 + 300 + 1000);
+
+        // Without timing data the duration is the circuit depth.
+        assert_eq!(Backend::linear(3).circuit_duration(&c), c.depth() as u64);
+    }
+
+    #[test]
+    fn test_weighted_distance_matrix() {
+        let mut backend = Backend::linear(3);
+        let hops = backend.weighted_distance_matrix();
+        assert_eq!(hops[0][2], 2.0);
+
+        backend.set_edge_error(0, 1, 0.5).unwrap();
+        backend.set_edge_error(2, 1, 0.5).unwrap();
+        let w = backend.weighted_distance_matrix();
+        assert!((w[0][2] - 2.0 * 2f64.ln()).abs() < 1e-12);
+        assert_eq!(backend.edge_error(1, 2), Some(0.5));
+
+        assert!(backend.set_edge_error(0, 2, 0.1).is_err());
+        assert!(backend.set_edge_error(0, 1, 1.0).is_err());
+
+        // An edge without a rate costs the mean of the recorded ones.
+        backend.set_edge_error(0, 1, 0.75).unwrap();
+        let mut wider = backend.clone();
+        wider.set_coupling_map([(0, 1), (1, 0), (1, 2), (2, 1), (0, 2)]);
+        let mean = (4f64.ln() + 2f64.ln()) / 2.0;
+        assert!((wider.edge_weight(0, 2) - mean).abs() < 1e-12);
+
+        // Replacing the coupling map drops the rates of removed edges.
+        wider.set_coupling_map([(1, 2), (2, 1)]);
+        assert_eq!(wider.edge_error(0, 1), None);
+        assert_eq!(wider.edge_error(1, 2), Some(0.5));
+        assert!((wider.edge_weight(1, 2) - 2f64.ln()).abs() < 1e-12);
+    }
+
     /// [E2E-NEW-FEATURE] Built-in IBM backends are constructed
     /// programmatically (no fixture file dependency).
     #[test]
@@ -433,6 +999,47 @@ mod tests {
         assert!(b.basis_gates.contains("cx"));
     }
 
+    #[test]
+    fn test_backend_json_round_trip() {
+        let b = Backend::ibm_quito();
+        let json = b.to_json().unwrap();
+        let back = Backend::from_json(&json).unwrap();
+        assert_eq!(back.name, b.name);
+        assert_eq!(back.num_qubits, b.num_qubits);
+        assert_eq!(back.basis_gates, b.basis_gates);
+        assert_eq!(back.to_config().coupling_map, b.to_config().coupling_map);
+
+        assert!(!json.contains("edge_errors"));
+        for u in 0..b.num_qubits {
+            for v in 0..b.num_qubits {
+                assert_eq!(back.has_directed_edge(u, v), b.has_directed_edge(u, v));
+            }
+        }
+
+        let mut noisy = Backend::linear(3);
+        noisy.set_edge_error(1, 0, 0.01).unwrap();
+        noisy.set_edge_error(1, 2, 0.02).unwrap();
+        let noisy_back = Backend::from_json(&noisy.to_json().unwrap()).unwrap();
+        assert_eq!(noisy_back.edge_error(0, 1), Some(0.01));
+        assert_eq!(noisy_back.edge_error(2, 1), Some(0.02));
+        assert_eq!(noisy_back.to_json().unwrap(), noisy.to_json().unwrap());
+
+        let mut timed = Backend::linear(2);
+        timed.gate_durations.insert("cx".into(), 300);
+        timed.gate_durations.insert("measure".into(), 1000);
+        timed.default_gate_duration = 35;
+        let timed_back = Backend::from_json(&timed.to_json().unwrap()).unwrap();
+        assert_eq!(timed_back.gate_durations, timed.gate_durations);
+        assert_eq!(timed_back.default_gate_duration, 35);
+        assert_eq!(back.default_gate_duration, 1);
+
+        assert!(back.supports_mid_circuit_measure);
+        let mut terminal_only = Backend::linear(2);
+        terminal_only.supports_mid_circuit_measure = false;
+        let terminal_back = Backend::from_json(&terminal_only.to_json().unwrap()).unwrap();
+        assert!(!terminal_back.supports_mid_circuit_measure);
+    }
+
     #[test]
     fn test_from_json_file_missing_returns_err() {
         let r = Backend::from_json_file("/nonexistent/path/foo.json");
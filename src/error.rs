@@ -1,27 +1,29 @@
 //! Unified error type for Q-Rust.
 //!
 //! Every fallible public API returns [`Result<T>`] (an alias for
-//! `std::result::Result<T, QRustError>`). Variants are tagged with
+//! `core::result::Result<T, QRustError>`). Variants are tagged with
 //! `#[non_exhaustive]` so adding new variants is not a breaking change.
 //!
 //!
 //! # Display contract
 //!
 //! Every variant renders to a single-line, human-readable message via the
-//! [`std::fmt::Display`] impl synthesized by `thiserror`, suitable for CLI
+//! [`core::fmt::Display`] impl synthesized by `thiserror`, suitable for CLI
 //! output without further processing. The exact wording (including
 //! capitalization) is part of the public API and is regression-tested by
 //! `test_every_error_variant_renders_human_readable` below.
 
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use thiserror::Error;
 
 /// Convenience alias: `Result<T, QRustError>`.
-pub type Result<T> = std::result::Result<T, QRustError>;
+pub type Result<T> = core::result::Result<T, QRustError>;
 
 /// The unified Q-Rust error type.
 ///
 /// All variants render to a human-readable single-line message via the
-/// [`std::fmt::Display`] impl synthesized by `thiserror`. The wording is
+/// [`core::fmt::Display`] impl synthesized by `thiserror`. The wording is
 /// stable across patch releases.
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -30,6 +32,12 @@ pub enum QRustError {
     #[error("QASM parse error: {0}")]
     ParseError(String),
 
+    /// The QASM source does not match the grammar; carries the line and
+    /// column of the offending text.
+    #[cfg(feature = "std")]
+    #[error("QASM parse error: {0}")]
+    Syntax(crate::parser::ParseError),
+
     /// A referenced register, qubit, or classical bit identifier was not
     /// declared in the circuit.
     #[error("Undefined identifier: {0}")]
@@ -133,6 +141,16 @@ pub enum QRustError {
         /// Physical qubit count provided by the backend.
         backend: usize,
     },
+
+    /// Raised by [`crate::ir::Circuit::verify_against`] when two circuits
+    /// implement different unitaries.
+    #[error("circuits are not equivalent: {0}")]
+    NotEquivalent(String),
+
+    /// Raised by [`crate::ir::Circuit::append`] for a gate with repeated
+    /// qubits or the wrong number of them.
+    #[error("invalid operation: {0}")]
+    InvalidOperation(String),
 }
 
 impl From<&str> for QRustError {
@@ -163,6 +181,15 @@ mod tests {
     fn test_every_error_variant_renders_human_readable() {
         let cases: Vec<(QRustError, &str)> = vec![
             (QRustError::ParseError("foo".into()), "parse error"),
+            (
+                QRustError::Syntax(crate::parser::ParseError {
+                    line: 2,
+                    column: 1,
+                    message: "parse error".into(),
+                    snippet: "foo".into(),
+                }),
+                "parse error near: foo (line 2, column 1)",
+            ),
             (QRustError::Undefined("x".into()), "undefined identifier"),
             (
                 QRustError::IndexOutOfBounds {
@@ -213,6 +240,14 @@ mod tests {
                 },
                 "insufficient qubits",
             ),
+            (
+                QRustError::NotEquivalent("max amplitude difference 0.3".into()),
+                "not equivalent",
+            ),
+            (
+                QRustError::InvalidOperation("cx repeats qubit 0".into()),
+                "invalid operation",
+            ),
         ];
         for (err, expected_substring) in cases {
             let rendered = err.to_string();
@@ -1,8 +1,15 @@
 //! Parser-internal AST for OpenQASM 2.0 statements and expressions.
 
 use crate::error::{QRustError, Result};
-use std::collections::HashMap;
-use std::f64::consts::PI;
+use crate::ir::HashMap;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::f64::consts::{E, PI, TAU};
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 #[derive(Debug, PartialEq, Clone)]
 #[non_exhaustive]
@@ -13,22 +20,26 @@ pub enum Expr {
     Sub(Box<Expr>, Box<Expr>),
     Mul(Box<Expr>, Box<Expr>),
     Div(Box<Expr>, Box<Expr>),
+    /// `base ^ exponent`.
+    Pow(Box<Expr>, Box<Expr>),
+    /// Unary function call: `sin`, `cos`, `tan`, `exp`, `ln` or `sqrt`.
+    Call(String, Box<Expr>),
 }
 
 impl Expr {
     pub fn evaluate_with_scope(&self, scope: &HashMap<String, f64>) -> Result<f64> {
         match self {
             Expr::Float(v) => Ok(*v),
-            Expr::Var(name) => {
-                if name == "pi" {
-                    Ok(PI)
-                } else {
-                    scope
-                        .get(name)
-                        .copied()
-                        .ok_or_else(|| QRustError::Undefined(name.clone()))
-                }
-            }
+            // `pi` is reserved; the newer constants can be shadowed by scope
+            // bindings (gate parameters), so existing code with a parameter
+            // called `e` keeps working.
+            Expr::Var(name) => match (scope.get(name), name.as_str()) {
+                (_, "pi") => Ok(PI),
+                (Some(&v), _) => Ok(v),
+                (None, "tau") => Ok(TAU),
+                (None, "euler" | "e") => Ok(E),
+                (None, _) => Err(QRustError::Undefined(name.clone())),
+            },
             Expr::Add(l, r) => Ok(l.evaluate_with_scope(scope)? + r.evaluate_with_scope(scope)?),
             Expr::Sub(l, r) => Ok(l.evaluate_with_scope(scope)? - r.evaluate_with_scope(scope)?),
             Expr::Mul(l, r) => Ok(l.evaluate_with_scope(scope)? * r.evaluate_with_scope(scope)?),
@@ -40,6 +51,48 @@ impl Expr {
                     Ok(l.evaluate_with_scope(scope)? / d)
                 }
             }
+            Expr::Pow(b, e) => Ok(b
+                .evaluate_with_scope(scope)?
+                .powf(e.evaluate_with_scope(scope)?)),
+            Expr::Call(name, arg) => {
+                let x = arg.evaluate_with_scope(scope)?;
+                match name.as_str() {
+                    "sin" => Ok(x.sin()),
+                    "cos" => Ok(x.cos()),
+                    "tan" => Ok(x.tan()),
+                    "exp" => Ok(x.exp()),
+                    "ln" if x <= 0.0 => Err(QRustError::Arithmetic(format!(
+                        "ln of non-positive value {x}"
+                    ))),
+                    "ln" => Ok(x.ln()),
+                    "sqrt" if x < 0.0 => Err(QRustError::Arithmetic(format!(
+                        "sqrt of negative value {x}"
+                    ))),
+                    "sqrt" => Ok(x.sqrt()),
+                    other => Err(QRustError::Undefined(format!(
+                        "function '{other}' (expected sin, cos, tan, exp, ln or sqrt)"
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Returns a copy with every variable named in `bindings` replaced by
+    /// its value. Other variables are left symbolic.
+    pub fn substitute(&self, bindings: &HashMap<String, f64>) -> Expr {
+        let sub = |e: &Expr| Box::new(e.substitute(bindings));
+        match self {
+            Expr::Float(v) => Expr::Float(*v),
+            Expr::Var(name) => match bindings.get(name) {
+                Some(&v) => Expr::Float(v),
+                None => Expr::Var(name.clone()),
+            },
+            Expr::Add(l, r) => Expr::Add(sub(l), sub(r)),
+            Expr::Sub(l, r) => Expr::Sub(sub(l), sub(r)),
+            Expr::Mul(l, r) => Expr::Mul(sub(l), sub(r)),
+            Expr::Div(l, r) => Expr::Div(sub(l), sub(r)),
+            Expr::Pow(b, e) => Expr::Pow(sub(b), sub(e)),
+            Expr::Call(name, arg) => Expr::Call(name.clone(), sub(arg)),
         }
     }
 
@@ -58,8 +111,14 @@ pub enum ParsedStatement {
     Measure((String, Option<usize>), (String, Option<usize>)),
     Include(String),
     Barrier(Vec<(String, Option<usize>)>),
+    /// `reset` over indexed and/or whole-register arguments.
+    Reset(Vec<(String, Option<usize>)>),
     GateDef(String, Vec<String>, Vec<String>, Vec<ParsedStatement>),
     If(String, usize, Box<ParsedStatement>),
+    /// Braced statement list; only produced as the body of an `If`.
+    Block(Vec<ParsedStatement>),
+    /// `// @name argument` pragma comment: (name, raw argument text).
+    Pragma(String, String),
     Ignore,
 }
 
@@ -77,6 +136,18 @@ mod tests {
         assert_eq!(Expr::Var("pi".into()).evaluate().unwrap(), PI);
     }
 
+    #[test]
+    fn test_expr_tau_and_euler_constants() {
+        assert!((Expr::Var("tau".into()).evaluate().unwrap() - 2.0 * PI).abs() < 1e-12);
+        assert_eq!(Expr::Var("euler".into()).evaluate().unwrap(), E);
+        assert_eq!(Expr::Var("e".into()).evaluate().unwrap(), E);
+        let scope = HashMap::from([("e".to_string(), 0.5)]);
+        assert_eq!(
+            Expr::Var("e".into()).evaluate_with_scope(&scope).unwrap(),
+            0.5
+        );
+    }
+
     #[test]
     fn test_expr_unknown_variable() {
         assert!(matches!(
@@ -91,12 +162,49 @@ mod tests {
         assert_eq!(e.evaluate().unwrap(), 5.0);
     }
 
+    #[test]
+    fn test_expr_function_calls() {
+        let call = |name: &str, x: f64| Expr::Call(name.into(), Box::new(Expr::Float(x)));
+        assert!((call("sin", PI / 2.0).evaluate().unwrap() - 1.0).abs() < 1e-12);
+        assert!((call("cos", 0.0).evaluate().unwrap() - 1.0).abs() < 1e-12);
+        assert!((call("tan", PI / 4.0).evaluate().unwrap() - 1.0).abs() < 1e-12);
+        assert!((call("exp", 1.0).evaluate().unwrap() - std::f64::consts::E).abs() < 1e-12);
+        assert!((call("ln", 1.0).evaluate().unwrap()).abs() < 1e-12);
+        assert!((call("sqrt", 4.0).evaluate().unwrap() - 2.0).abs() < 1e-12);
+        assert!(matches!(
+            call("ln", 0.0).evaluate(),
+            Err(QRustError::Arithmetic(_))
+        ));
+        assert!(matches!(
+            call("sqrt", -1.0).evaluate(),
+            Err(QRustError::Arithmetic(_))
+        ));
+        let err = call("cosh", 0.0).evaluate().unwrap_err();
+        assert!(err.to_string().contains("cosh"), "{err}");
+    }
+
     #[test]
     fn test_expr_division_by_zero() {
         let e = Expr::Div(Box::new(Expr::Float(10.0)), Box::new(Expr::Float(0.0)));
         assert!(matches!(e.evaluate(), Err(QRustError::Arithmetic(_))));
     }
 
+    #[test]
+    fn test_expr_substitute_leaves_unbound_vars() {
+        let e = Expr::Mul(
+            Box::new(Expr::Var("k".into())),
+            Box::new(Expr::Var("theta".into())),
+        );
+        let bound = e.substitute(&HashMap::from([("k".to_string(), 2.0)]));
+        assert_eq!(
+            bound,
+            Expr::Mul(
+                Box::new(Expr::Float(2.0)),
+                Box::new(Expr::Var("theta".into()))
+            )
+        );
+    }
+
     #[test]
     fn test_expr_complex_expression() {
         let e = Expr::Add(
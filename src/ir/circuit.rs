@@ -2,10 +2,58 @@
 
 use super::ast::ParsedStatement;
 use super::gates::GateType;
-use super::operations::Operation;
+use super::operations::{Operation, QasmStyle};
 use super::registry::GateRegistry;
-use std::collections::HashMap;
-use std::fmt;
+use crate::error::{QRustError, Result};
+use crate::ir::{HashMap, HashSet};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use core::ops::Range;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
+
+/// A named register: the contiguous slice `[start, start + size)` of a
+/// circuit's flat qubit or cbit index space.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-ir", derive(serde::Serialize, serde::Deserialize))]
+pub struct Register {
+    pub name: String,
+    pub start: usize,
+    pub size: usize,
+}
+
+impl Register {
+    pub fn new(name: impl Into<String>, start: usize, size: usize) -> Self {
+        Self {
+            name: name.into(),
+            start,
+            size,
+        }
+    }
+
+    /// Returns true if the flat index `index` falls inside this register.
+    #[inline]
+    pub fn contains(&self, index: usize) -> bool {
+        index >= self.start && index < self.start + self.size
+    }
+}
+
+/// Returns true if `regs` are laid out back-to-back from 0 and cover exactly
+/// `0..len`, i.e. they can be emitted as declarations in order.
+fn registers_tile(regs: &[Register], len: usize) -> bool {
+    let mut next = 0;
+    for r in regs {
+        if r.start != next {
+            return false;
+        }
+        next += r.size;
+    }
+    next == len
+}
 
 #[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "serde-ir", derive(serde::Serialize, serde::Deserialize))]
@@ -15,6 +63,25 @@ pub struct Circuit {
     pub operations: Vec<Operation>,
     #[cfg_attr(feature = "serde-ir", serde(skip))]
     pub custom_gates: GateRegistry,
+    /// Global phase in radians, set via the `// @gphase <expr>` pragma and
+    /// re-emitted by [`Circuit::to_qasm`]. It has no observable effect on
+    /// measurement statistics, so transpiler passes carry it over (see
+    /// [`Circuit::empty_like`]) but do not track phase they introduce.
+    #[cfg_attr(feature = "serde-ir", serde(default))]
+    pub global_phase: f64,
+    /// Declared quantum registers, in declaration order. Empty for circuits
+    /// built programmatically; export then falls back to a flat `q`.
+    #[cfg_attr(feature = "serde-ir", serde(default))]
+    pub qregs: Vec<Register>,
+    /// Declared classical registers, in declaration order (flat `c` when
+    /// empty).
+    #[cfg_attr(feature = "serde-ir", serde(default))]
+    pub cregs: Vec<Register>,
+    /// Named operation ranges tagged with `// @region-begin name` /
+    /// `// @region-end name`; see [`Circuit::region`]. Indices refer to
+    /// `operations`, so transpiler passes do not carry regions over.
+    #[cfg_attr(feature = "serde-ir", serde(default))]
+    pub regions: BTreeMap<String, Range<usize>>,
 }
 
 impl Circuit {
@@ -24,7 +91,73 @@ impl Circuit {
             num_cbits,
             operations: Vec::new(),
             custom_gates: GateRegistry::new(),
+            global_phase: 0.0,
+            qregs: Vec::new(),
+            cregs: Vec::new(),
+            regions: BTreeMap::new(),
+        }
+    }
+
+    /// Returns an empty circuit with the same width, custom gates, global
+    /// phase and registers as `self`: the starting point for a pass that
+    /// rebuilds the operation list. Regions are not carried over.
+    pub fn empty_like(&self) -> Self {
+        Self {
+            num_qubits: self.num_qubits,
+            num_cbits: self.num_cbits,
+            operations: Vec::new(),
+            custom_gates: self.custom_gates.clone(),
+            global_phase: self.global_phase,
+            qregs: self.qregs.clone(),
+            cregs: self.cregs.clone(),
+            regions: BTreeMap::new(),
+        }
+    }
+
+    /// Collapses the register metadata into a single `q[num_qubits]` /
+    /// `c[num_cbits]` pair so the circuit exports with flat registers.
+    ///
+    /// Conditions on a classical register spanning every cbit are renamed
+    /// to `c`. A condition on a partial register cannot be expressed after
+    /// merging, so in that case the classical registers are left as-is.
+    pub fn merge_registers(&mut self) {
+        self.qregs = if self.num_qubits > 0 {
+            vec![Register::new("q", 0, self.num_qubits)]
+        } else {
+            Vec::new()
+        };
+
+        let full: Vec<String> = self
+            .cregs
+            .iter()
+            .filter(|r| r.start == 0 && r.size == self.num_cbits)
+            .map(|r| r.name.clone())
+            .collect();
+        fn conditions(op: &mut Operation, f: &mut dyn FnMut(&mut String)) {
+            if let Operation::Conditional { condition, op } = op {
+                f(&mut condition.creg);
+                conditions(op, f);
+            }
+        }
+        let mut mergeable = true;
+        for op in &mut self.operations {
+            conditions(op, &mut |creg| {
+                if !full.contains(creg) && creg != "c" {
+                    mergeable = false;
+                }
+            });
+        }
+        if !mergeable {
+            return;
         }
+        for op in &mut self.operations {
+            conditions(op, &mut |creg| *creg = "c".to_string());
+        }
+        self.cregs = if self.num_cbits > 0 {
+            vec![Register::new("c", 0, self.num_cbits)]
+        } else {
+            Vec::new()
+        };
     }
 
     pub fn register_custom_gate(
@@ -37,11 +170,454 @@ impl Circuit {
         self.custom_gates.register(name, params, qubits, body);
     }
 
+    /// Registers a matrix-defined custom gate; see
+    /// [`GateRegistry::register_unitary`] for the accepted matrices.
+    pub fn register_custom_unitary(
+        &mut self,
+        name: String,
+        matrix: nalgebra::DMatrix<num_complex::Complex<f64>>,
+    ) -> Result<()> {
+        self.custom_gates.register_unitary(name, matrix)
+    }
+
     #[inline]
     pub fn add_op(&mut self, op: Operation) {
         self.operations.push(op);
     }
 
+    /// Validating counterpart of [`Circuit::add_op`]: `op` must pass the
+    /// same per-operation checks as [`Circuit::check`].
+    ///
+    /// # Errors
+    /// Returns [`QRustError::IndexOutOfBounds`] (reported against the
+    /// flattened `q`/`c` registers) if any qubit or cbit index of `op` is
+    /// outside `num_qubits`/`num_cbits`, and
+    /// [`QRustError::InvalidOperation`] for a gate with repeated qubits or
+    /// the wrong arity. The circuit is unchanged on error.
+    pub fn append(&mut self, op: Operation) -> Result<()> {
+        if let Some(violation) = self.op_violations(&op).into_iter().next() {
+            return Err(violation.into());
+        }
+        self.add_op(op);
+        Ok(())
+    }
+
+    /// Measures `qubit` in the X basis into `cbit`: appends `h` and then a
+    /// computational-basis measurement, so `|+⟩` reads 0 and `|−⟩` reads 1.
+    ///
+    /// # Errors
+    /// As [`Circuit::append`]; the circuit is unchanged on error.
+    pub fn measure_x(&mut self, qubit: usize, cbit: usize) -> Result<()> {
+        self.measure_after(&[GateType::H], qubit, cbit)
+    }
+
+    /// Measures `qubit` in the Y basis into `cbit`: appends `sdg`, `h` and
+    /// then a computational-basis measurement, so `|+i⟩` reads 0 and `|−i⟩`
+    /// reads 1.
+    ///
+    /// # Errors
+    /// As [`Circuit::append`]; the circuit is unchanged on error.
+    pub fn measure_y(&mut self, qubit: usize, cbit: usize) -> Result<()> {
+        self.measure_after(&[GateType::Sdg, GateType::H], qubit, cbit)
+    }
+
+    /// Measures every qubit `i` into classical bit `i`, like Qiskit's
+    /// `measure_all`.
+    ///
+    /// If there are fewer classical bits than qubits, `num_cbits` grows to
+    /// `num_qubits`. The new bits get a register: `c` when the circuit has
+    /// no classical registers yet, otherwise a fresh `meas` register.
+    pub fn measure_all(&mut self) {
+        if self.num_cbits < self.num_qubits {
+            let added = self.num_qubits - self.num_cbits;
+            if self.cregs.is_empty() {
+                self.cregs.push(Register::new("c", 0, self.num_qubits));
+            } else {
+                let mut name = "meas".to_string();
+                let mut n = 0;
+                while self.cregs.iter().any(|r| r.name == name) {
+                    n += 1;
+                    name = format!("meas{n}");
+                }
+                self.cregs.push(Register::new(name, self.num_cbits, added));
+            }
+            self.num_cbits = self.num_qubits;
+        }
+        for qubit in 0..self.num_qubits {
+            self.add_op(Operation::Measure { qubit, cbit: qubit });
+        }
+    }
+
+    /// Appends the single-qubit `basis_change` gates on `qubit`, then a
+    /// measurement into `cbit`.
+    fn measure_after(
+        &mut self,
+        basis_change: &[GateType],
+        qubit: usize,
+        cbit: usize,
+    ) -> Result<()> {
+        // Validate via the measurement first so a bad index leaves no gates.
+        self.append(Operation::Measure { qubit, cbit })?;
+        let measure = self.operations.pop();
+        for gate in basis_change {
+            self.add_op(Operation::Gate {
+                name: gate.clone(),
+                qubits: vec![qubit],
+                params: vec![],
+            });
+        }
+        self.operations.extend(measure);
+        Ok(())
+    }
+
+    /// Relabels every qubit operand `q` as `perm[q]`.
+    ///
+    /// Meant for tracking the accumulated relabeling from routing SWAPs (e.g.
+    /// a `final_layout`), so `perm` must be a bijection on
+    /// `0..num_qubits`; unlike a general index remap it can never merge two
+    /// wires or change the qubit count.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::SizeMismatch`] if `perm.len() != num_qubits`
+    /// and [`QRustError::InvalidConfig`] if `perm` is not a permutation. The
+    /// circuit is unchanged on error.
+    pub fn apply_permutation(&mut self, perm: &[usize]) -> Result<()> {
+        if perm.len() != self.num_qubits {
+            return Err(QRustError::SizeMismatch(format!(
+                "permutation has {} entries but the circuit has {} qubits",
+                perm.len(),
+                self.num_qubits
+            )));
+        }
+        let mut seen = vec![false; perm.len()];
+        for &p in perm {
+            if p >= perm.len() || core::mem::replace(&mut seen[p], true) {
+                return Err(QRustError::InvalidConfig(format!(
+                    "{perm:?} is not a permutation of 0..{}",
+                    perm.len()
+                )));
+            }
+        }
+        for op in &mut self.operations {
+            op.map_qubits(&mut |q| perm[q]);
+        }
+        Ok(())
+    }
+
+    /// Appends every operation of `other`, sending its qubit `q` to
+    /// `qubit_map[q]` and classical bit `b` to `cbit_map[b]`. Custom gate
+    /// definitions of `other` are copied over unless `self` already defines
+    /// the name, and `other`'s global phase is added to `self`'s. `self`
+    /// never grows: the maps must point inside its existing qubits and bits.
+    ///
+    /// A conditional in `other` is kept only if its register's bits map
+    /// onto the bits of the same-named register in `self`, in order.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::SizeMismatch`] if a map is shorter than
+    /// `other`'s width, [`QRustError::InvalidConfig`] if a map sends two
+    /// indices to the same target or points outside `self`, and
+    /// [`QRustError::Unsupported`] for a conditional whose register does not
+    /// line up. `self` is unchanged on error.
+    pub fn compose(
+        &mut self,
+        other: &Circuit,
+        qubit_map: &[usize],
+        cbit_map: &[usize],
+    ) -> Result<()> {
+        for (kind, map, needed, available) in [
+            ("qubit", qubit_map, other.num_qubits, self.num_qubits),
+            ("cbit", cbit_map, other.num_cbits, self.num_cbits),
+        ] {
+            if map.len() < needed {
+                return Err(QRustError::SizeMismatch(format!(
+                    "{kind} map has {} entries but the composed circuit has {needed} {kind}s",
+                    map.len()
+                )));
+            }
+            let mut seen = vec![false; available];
+            for &target in &map[..needed] {
+                if target >= available {
+                    return Err(QRustError::InvalidConfig(format!(
+                        "{kind} map target {target} is out of range for {available} {kind}s"
+                    )));
+                }
+                if core::mem::replace(&mut seen[target], true) {
+                    return Err(QRustError::InvalidConfig(format!(
+                        "{kind} map sends two {kind}s to {target}"
+                    )));
+                }
+            }
+        }
+
+        let mut ops = Vec::with_capacity(other.operations.len());
+        for op in &other.operations {
+            let mut op = op.clone();
+            let mut inner = &mut op;
+            while let Operation::Conditional { condition, op } = inner {
+                let lines_up = match (
+                    other.creg_bits(&condition.creg),
+                    self.creg_bits(&condition.creg),
+                ) {
+                    (Some(src), Some(dst)) => src.map(|b| cbit_map[b]).eq(dst),
+                    _ => false,
+                };
+                if !lines_up {
+                    return Err(QRustError::Unsupported(format!(
+                        "condition on '{}' does not map onto the same register",
+                        condition.creg
+                    )));
+                }
+                inner = op;
+            }
+            if let Operation::Measure { cbit, .. } = inner {
+                *cbit = cbit_map[*cbit];
+            }
+            op.map_qubits(&mut |q| qubit_map[q]);
+            ops.push(op);
+        }
+
+        for (name, def) in &other.custom_gates.defs {
+            self.custom_gates
+                .defs
+                .entry(name.clone())
+                .or_insert_with(|| def.clone());
+        }
+        for (name, u) in &other.custom_gates.unitaries {
+            self.custom_gates
+                .unitaries
+                .entry(name.clone())
+                .or_insert_with(|| u.clone());
+        }
+        self.global_phase += other.global_phase;
+        self.operations.extend(ops);
+        Ok(())
+    }
+
+    /// Returns the adjoint circuit: operations in reverse order, each gate
+    /// replaced by its [`GateType::inverse`] with
+    /// [`GateType::inverse_params`], and the global phase negated.
+    /// Barriers and classical conditions are kept, and regions are mirrored
+    /// onto the reversed indices.
+    ///
+    /// Gates without a built-in adjoint are expanded: `csx` becomes `crx`
+    /// plus a phase on the control, `iswap` becomes `rxx` and `ryy`, and
+    /// custom gates are inlined from their definitions and inverted gate by
+    /// gate. A matrix-defined custom gate becomes `<name>_dg`, registered
+    /// with the adjoint matrix. The result therefore exports to QASM that
+    /// parses back.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::Unsupported`] naming the first measurement or
+    /// reset, as neither is invertible, and [`QRustError::Decomposition`]
+    /// for a custom gate with no definition.
+    #[cfg(feature = "std")]
+    pub fn inverse(&self) -> Result<Circuit> {
+        use alloc::boxed::Box;
+        use core::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+        fn invert(
+            op: &Operation,
+            index: usize,
+            source: &Circuit,
+            adjoints: &mut GateRegistry,
+            out: &mut Vec<Operation>,
+        ) -> Result<()> {
+            let gate = |name, qubits: &[usize], params| Operation::Gate {
+                name,
+                qubits: qubits.to_vec(),
+                params,
+            };
+            match op {
+                Operation::Gate {
+                    name,
+                    qubits,
+                    params,
+                } => match name {
+                    GateType::CSX => {
+                        // CSX = C(e^{iπ/4} RX(π/2)), so its adjoint is a
+                        // CRX(-π/2) and a -π/4 phase on the control.
+                        out.push(gate(GateType::CRX, qubits, vec![-FRAC_PI_2]));
+                        out.push(gate(GateType::U, &qubits[..1], vec![0.0, 0.0, -FRAC_PI_4]));
+                    }
+                    GateType::ISwap => {
+                        // iSWAP = exp(iπ/4 (XX + YY)) and XX, YY commute.
+                        out.push(gate(GateType::RXX, qubits, vec![FRAC_PI_2]));
+                        out.push(gate(GateType::RYY, qubits, vec![FRAC_PI_2]));
+                    }
+                    GateType::Custom(n) if source.custom_gates.get(n).is_none() => {
+                        let Some(u) = source.custom_gates.unitary(n) else {
+                            return Err(QRustError::Decomposition(format!(
+                                "custom gate '{n}' not in registry"
+                            )));
+                        };
+                        let dg = name.inverse().to_string();
+                        adjoints.register_unitary(dg.clone(), u.adjoint())?;
+                        out.push(gate(GateType::Custom(dg), qubits, params.clone()));
+                    }
+                    GateType::Custom(_) => {
+                        let mut single = Circuit::new(source.num_qubits, source.num_cbits);
+                        single.add_op(op.clone());
+                        let unrolled =
+                            crate::transpiler::decomposition::try_unroll_custom_gates_with(
+                                &single,
+                                &source.custom_gates,
+                            )?;
+                        for sub in unrolled.operations.iter().rev() {
+                            invert(sub, index, source, adjoints, out)?;
+                        }
+                    }
+                    _ => out.push(gate(name.inverse(), qubits, name.inverse_params(params))),
+                },
+                Operation::Barrier { .. } => out.push(op.clone()),
+                Operation::Conditional { condition, op } => {
+                    let mut inner = Vec::new();
+                    invert(op, index, source, adjoints, &mut inner)?;
+                    out.extend(inner.into_iter().map(|op| Operation::Conditional {
+                        condition: condition.clone(),
+                        op: Box::new(op),
+                    }));
+                }
+                Operation::Measure { .. } | Operation::Reset { .. } => {
+                    return Err(QRustError::Unsupported(format!(
+                        "cannot invert operation {index} ({op:?}): measurements and resets are not unitary"
+                    )));
+                }
+            }
+            Ok(())
+        }
+
+        let n = self.operations.len();
+        let mut inverse = self.clone();
+        let mut operations = Vec::with_capacity(n);
+        // `bounds[k]` is where the inverse of `operations[k..]` ends, so a
+        // region `a..b` maps to `bounds[b]..bounds[a]`.
+        let mut bounds = vec![0; n + 1];
+        for (i, op) in self.operations.iter().enumerate().rev() {
+            invert(op, i, self, &mut inverse.custom_gates, &mut operations)?;
+            bounds[i] = operations.len();
+        }
+        inverse.operations = operations;
+        inverse.global_phase = -self.global_phase;
+        for range in inverse.regions.values_mut() {
+            *range = bounds[range.end]..bounds[range.start];
+        }
+        Ok(inverse)
+    }
+
+    /// Drops the classical bits no measurement writes and renumbers the
+    /// rest in order, updating measurement targets, `num_cbits` and the
+    /// classical registers (emptied registers are removed). Bits of a
+    /// register tested by a conditional are always kept, so conditions keep
+    /// their meaning.
+    ///
+    /// Returns the old-to-new map: `map[old] == Some(new)` for kept bits,
+    /// `None` for dropped ones.
+    pub fn reindex_cbits(&mut self) -> Vec<Option<usize>> {
+        let mut keep = vec![false; self.num_cbits];
+        for op in &self.operations {
+            let mut op = op;
+            while let Operation::Conditional {
+                condition,
+                op: inner,
+            } = op
+            {
+                // An unknown register conservatively keeps every bit.
+                let bits = self.creg_bits(&condition.creg).unwrap_or(0..self.num_cbits);
+                for b in bits {
+                    if let Some(k) = keep.get_mut(b) {
+                        *k = true;
+                    }
+                }
+                op = inner;
+            }
+            if let Operation::Measure { cbit, .. } = op {
+                if let Some(k) = keep.get_mut(*cbit) {
+                    *k = true;
+                }
+            }
+        }
+
+        let mut next = 0;
+        let map: Vec<Option<usize>> = keep
+            .iter()
+            .map(|&k| {
+                k.then(|| {
+                    next += 1;
+                    next - 1
+                })
+            })
+            .collect();
+
+        fn remap(op: &mut Operation, map: &[Option<usize>]) {
+            match op {
+                Operation::Measure { cbit, .. } => {
+                    if let Some(Some(new)) = map.get(*cbit) {
+                        *cbit = *new;
+                    }
+                }
+                Operation::Conditional { op, .. } => remap(op, map),
+                _ => {}
+            }
+        }
+        for op in &mut self.operations {
+            remap(op, &map);
+        }
+        for reg in &mut self.cregs {
+            let kept: Vec<usize> = (reg.start..reg.start + reg.size)
+                .filter_map(|b| map.get(b).copied().flatten())
+                .collect();
+            reg.start = kept.first().copied().unwrap_or(0);
+            reg.size = kept.len();
+        }
+        self.cregs.retain(|r| r.size > 0);
+        self.num_cbits = next;
+        map
+    }
+
+    /// Number of operations (top-level; a conditional counts once).
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Returns `true` if the circuit has no operations.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// The operation at `idx`, or `None` past the end.
+    pub fn gate_at(&self, idx: usize) -> Option<&Operation> {
+        self.operations.get(idx)
+    }
+
+    /// Qubits referenced by any gate, measurement or reset, including
+    /// inside conditionals. Barriers are ignored: they do not act on the
+    /// state, and a `barrier q;` would otherwise mark every qubit as used.
+    pub fn qubits_used(&self) -> HashSet<usize> {
+        self.operations
+            .iter()
+            .filter(|op| !op.is_barrier())
+            .flat_map(|op| op.qubits().iter().copied())
+            .collect()
+    }
+
+    /// Declared qubits that no operation other than a barrier touches, in
+    /// ascending order; see [`Circuit::qubits_used`].
+    pub fn idle_qubits(&self) -> Vec<usize> {
+        let used = self.qubits_used();
+        (0..self.num_qubits).filter(|q| !used.contains(q)).collect()
+    }
+
+    /// Returns the indices of all operations for which `pred` holds.
+    pub fn find(&self, pred: impl Fn(&Operation) -> bool) -> Vec<usize> {
+        self.operations
+            .iter()
+            .enumerate()
+            .filter(|(_, op)| pred(op))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     pub fn validate(&self) -> Vec<String> {
         let mut warnings = Vec::new();
         let has_measurement = self
@@ -55,16 +631,250 @@ impl Circuit {
                     .to_string(),
             );
         }
+
+        // Double-measure check: a qubit measured again with nothing acting on
+        // it in between. A reset (or any gate) between the two measurements is
+        // a legitimate mid-circuit pattern and clears the pending measure.
+        //
+        // The same pass flags an overwritten cbit: two measurements into the
+        // same classical bit with no conditional reading it in between, so
+        // the first result is lost (usually a copy-paste slip in hand-written
+        // QASM). A pair that is already a double measure of the same qubit
+        // is only reported once.
+        let mut last_measure: HashMap<usize, usize> = HashMap::new();
+        let mut last_write: HashMap<usize, usize> = HashMap::new();
+        for (i, op) in self.operations.iter().enumerate() {
+            match op {
+                Operation::Measure { qubit, cbit } => {
+                    let prev_measure = last_measure.insert(*qubit, i);
+                    let prev_write = last_write.insert(*cbit, i);
+                    if let Some(prev) = prev_measure {
+                        warnings.push(format!(
+                            "Warning: qubit {} is measured twice (ops #{} and #{}) \
+                             without an intervening reset.",
+                            qubit, prev, i
+                        ));
+                    }
+                    if let Some(prev) = prev_write.filter(|&p| Some(p) != prev_measure) {
+                        warnings.push(format!(
+                            "Warning: cbit {} is written by ops #{} and #{} \
+                             without being read in between; the first result is \
+                             overwritten.",
+                            cbit, prev, i
+                        ));
+                    }
+                }
+                Operation::Barrier { .. } => {}
+                other => {
+                    for q in other.qubits() {
+                        last_measure.remove(q);
+                    }
+                    if let Operation::Conditional { condition, .. } = other {
+                        match self.creg_bits(&condition.creg) {
+                            Some(bits) => last_write.retain(|c, _| !bits.contains(c)),
+                            None => last_write.clear(),
+                        }
+                    }
+                }
+            }
+        }
         warnings
     }
 
+    /// Checks that the circuit is well-formed, collecting every violation
+    /// rather than stopping at the first: qubit indices must be below
+    /// `num_qubits`, measurement targets below `num_cbits`, and each gate
+    /// must have as many qubits as [`GateType::num_qubits`] (or, for custom
+    /// gates, the registered definition) requires, all distinct.
+    /// Conditionals are checked through their inner operation.
+    ///
+    /// # Errors
+    /// One message per violation, prefixed with the operation index.
+    pub fn check(&self) -> core::result::Result<(), Vec<String>> {
+        let errors: Vec<String> = self
+            .operations
+            .iter()
+            .enumerate()
+            .flat_map(|(i, op)| {
+                self.op_violations(op)
+                    .into_iter()
+                    .map(move |v| format!("op #{i}: {v}"))
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Per-operation half of [`Circuit::check`], shared with
+    /// [`Circuit::append`].
+    fn op_violations(&self, op: &Operation) -> Vec<OpViolation> {
+        let mut violations = Vec::new();
+        let mut op = op;
+        while let Operation::Conditional { op: inner, .. } = op {
+            op = inner;
+        }
+        for &q in op.qubits() {
+            if q >= self.num_qubits {
+                violations.push(OpViolation::Qubit {
+                    index: q,
+                    size: self.num_qubits,
+                });
+            }
+        }
+        match op {
+            Operation::Measure { cbit, .. } if *cbit >= self.num_cbits => {
+                violations.push(OpViolation::Cbit {
+                    index: *cbit,
+                    size: self.num_cbits,
+                });
+            }
+            Operation::Gate { name, qubits, .. } => {
+                for (k, q) in qubits.iter().enumerate() {
+                    if qubits[..k].iter().filter(|&p| p == q).count() == 1 {
+                        violations.push(OpViolation::RepeatedQubit {
+                            gate: name.to_string(),
+                            qubit: *q,
+                        });
+                    }
+                }
+                let arity = name.num_qubits().or_else(|| {
+                    let GateType::Custom(n) = name else {
+                        return None;
+                    };
+                    self.custom_gates
+                        .get(n)
+                        .map(|d| d.qubits.len())
+                        .or_else(|| {
+                            self.custom_gates
+                                .unitary(n)
+                                .map(|m| m.nrows().trailing_zeros() as usize)
+                        })
+                });
+                if let Some(arity) = arity.filter(|&a| a != qubits.len()) {
+                    violations.push(OpViolation::Arity {
+                        gate: name.to_string(),
+                        arity,
+                        applied: qubits.len(),
+                    });
+                }
+            }
+            _ => {}
+        }
+        violations
+    }
+
+    /// Exports the circuit as OpenQASM 2.0 (with `include "qelib1.inc"`)
+    /// that [`crate::parser::parse_qasm`] reads back to the same operations:
+    /// parameters are written with full precision, and the global phase and
+    /// declared registers are kept. With a `property_set`, the initial and
+    /// final layouts are recorded as comments.
+    #[cfg(feature = "std")]
     pub fn to_qasm(
         &self,
         property_set: Option<&crate::transpiler::property_set::PropertySet>,
+    ) -> String {
+        self.render_qasm(property_set, false)
+    }
+
+    /// Returns `true` if every gate (conditional ones included) is one of
+    /// the OpenQASM 2.0 built-ins `U` and `CX`; measurements, resets and
+    /// barriers are always allowed. This is what
+    /// [`Circuit::to_qasm_primitive`] emits.
+    pub fn is_primitive(&self) -> bool {
+        fn primitive(op: &Operation) -> bool {
+            match op {
+                Operation::Gate { name, .. } => matches!(name, GateType::U | GateType::CX),
+                Operation::Conditional { op, .. } => primitive(op),
+                Operation::Measure { .. } | Operation::Reset { .. } | Operation::Barrier { .. } => {
+                    true
+                }
+            }
+        }
+        self.operations.iter().all(primitive)
+    }
+
+    /// Exports the circuit using only the OpenQASM 2.0 built-ins `U` and
+    /// `CX`, so the result parses without `include "qelib1.inc"`.
+    ///
+    /// Every other gate (including custom gates) is first lowered through
+    /// [`crate::transpiler::decomposition::try_decompose_basis`]; an error
+    /// is returned when that lowering fails.
+    #[cfg(feature = "std")]
+    pub fn to_qasm_primitive(&self) -> crate::error::Result<String> {
+        let mut lowered = crate::transpiler::decomposition::try_decompose_basis(self)?;
+        lowered.global_phase = self.global_phase;
+        lowered.qregs = self.qregs.clone();
+        lowered.cregs = self.cregs.clone();
+        Ok(lowered.render_qasm(None, true))
+    }
+
+    /// Experimental OpenQASM 3 export (write-only; there is no QASM 3
+    /// parser). Registers become `qubit[n]`/`bit[n]` declarations,
+    /// measurements `c[i] = measure q[j];`, conditionals
+    /// `if (c == v) { ... }` and a non-zero global phase a `gphase`
+    /// statement. Gates keep their QASM 2 names, with `u` spelled as the
+    /// built-in `U`; gates outside `stdgates.inc` (e.g. `rzz`, `ecr`, custom
+    /// gates) are emitted by name without a definition.
+    pub fn to_qasm3(&self) -> String {
+        let mut qasm = String::with_capacity(256 + self.operations.len() * 24);
+        qasm.push_str("OPENQASM 3.0;\n");
+        qasm.push_str("include \"stdgates.inc\";\n\n");
+
+        let named_q = !self.qregs.is_empty() && registers_tile(&self.qregs, self.num_qubits);
+        let named_c = !self.cregs.is_empty() && registers_tile(&self.cregs, self.num_cbits);
+        let style = QasmStyle {
+            primitive: false,
+            qregs: if named_q { &self.qregs } else { &[] },
+            cregs: if named_c { &self.cregs } else { &[] },
+            qasm3: true,
+        };
+
+        if named_q {
+            for r in &self.qregs {
+                qasm.push_str(&format!("qubit[{}] {};\n", r.size, r.name));
+            }
+        } else {
+            qasm.push_str(&format!("qubit[{}] q;\n", self.num_qubits));
+        }
+        if named_c {
+            for r in &self.cregs {
+                qasm.push_str(&format!("bit[{}] {};\n", r.size, r.name));
+            }
+        } else if self.num_cbits > 0 {
+            qasm.push_str(&format!("bit[{}] c;\n", self.num_cbits));
+        }
+        qasm.push('\n');
+
+        if self.global_phase != 0.0 {
+            qasm.push_str(&format!("gphase({});\n", self.global_phase));
+        }
+        for op in &self.operations {
+            // Writing into a String is infallible.
+            let _ = op.write_qasm_with(&mut qasm, &style);
+            qasm.push('\n');
+        }
+        qasm
+    }
+
+    #[cfg(feature = "std")]
+    fn render_qasm(
+        &self,
+        property_set: Option<&crate::transpiler::property_set::PropertySet>,
+        primitive: bool,
     ) -> String {
         let mut qasm = String::with_capacity(256 + self.operations.len() * 24);
         qasm.push_str("OPENQASM 2.0;\n");
-        qasm.push_str("include \"qelib1.inc\";\n\n");
+        if !primitive {
+            qasm.push_str("include \"qelib1.inc\";\n");
+        }
+        qasm.push('\n');
+
+        if self.global_phase != 0.0 {
+            qasm.push_str(&format!("// @gphase {}\n", self.global_phase));
+        }
 
         if let Some(props) = property_set {
             if let Some(initial) = props.get::<Vec<usize>>("initial_layout") {
@@ -75,92 +885,519 @@ impl Circuit {
             }
         }
 
-        qasm.push_str(&format!("qreg q[{}];\n", self.num_qubits));
-        if self.num_cbits > 0 {
+        // Named registers are only used when they exactly tile the flat
+        // index space; otherwise fall back to a single `q`/`c` pair.
+        let named_q = !self.qregs.is_empty() && registers_tile(&self.qregs, self.num_qubits);
+        let named_c = !self.cregs.is_empty() && registers_tile(&self.cregs, self.num_cbits);
+        let style = QasmStyle {
+            primitive,
+            qregs: if named_q { &self.qregs } else { &[] },
+            cregs: if named_c { &self.cregs } else { &[] },
+            qasm3: false,
+        };
+
+        if named_q {
+            for r in &self.qregs {
+                qasm.push_str(&format!("qreg {}[{}];\n", r.name, r.size));
+            }
+        } else {
+            qasm.push_str(&format!("qreg q[{}];\n", self.num_qubits));
+        }
+        if named_c {
+            for r in &self.cregs {
+                qasm.push_str(&format!("creg {}[{}];\n", r.name, r.size));
+            }
+        } else if self.num_cbits > 0 {
             qasm.push_str(&format!("creg c[{}];\n", self.num_cbits));
         }
         qasm.push('\n');
 
         for op in &self.operations {
-            qasm.push_str(&op.to_qasm());
+            // Writing into a String is infallible.
+            let _ = op.write_qasm_with(&mut qasm, &style);
             qasm.push('\n');
         }
         qasm
     }
 
-    /// Returns the depth (critical-path length). Barriers bump each affected
-    /// wire's depth tracker to the max so subsequent ops line up after the
-    /// barrier, but barriers themselves don't add depth.
-    pub fn depth(&self) -> usize {
-        let mut qd = vec![0usize; self.num_qubits];
-        for op in &self.operations {
-            match op {
-                Operation::Gate { qubits, .. } => {
-                    let m = qubits
-                        .iter()
-                        .filter_map(|&q| qd.get(q).copied())
-                        .max()
-                        .unwrap_or(0);
-                    let new_d = m + 1;
-                    for &q in qubits {
-                        if let Some(slot) = qd.get_mut(q) {
-                            *slot = new_d;
-                        }
-                    }
-                }
-                Operation::Measure { qubit, .. } | Operation::Reset { qubit } => {
-                    if let Some(slot) = qd.get_mut(*qubit) {
-                        *slot += 1;
-                    }
+    /// Extracts the operations tagged as region `name` into a circuit of the
+    /// same width, e.g. to optimize or simulate one part of a larger
+    /// program. Returns `None` if no such region exists.
+    pub fn region(&self, name: &str) -> Option<Circuit> {
+        let range = self.regions.get(name)?.clone();
+        let mut sub = Circuit::new(self.num_qubits, self.num_cbits);
+        sub.custom_gates = self.custom_gates.clone();
+        sub.qregs = self.qregs.clone();
+        sub.cregs = self.cregs.clone();
+        sub.operations = self.operations.get(range)?.to_vec();
+        Some(sub)
+    }
+
+    /// Extracts the operations acting entirely within `qubits` into a
+    /// `qubits.len()`-qubit circuit, renumbering `qubits[i]` to `i`, e.g. to
+    /// simulate one register in isolation. Operations that do not touch the
+    /// subset are dropped, and barriers are narrowed to it. Classical bits,
+    /// classical registers and custom gates are kept as they are.
+    ///
+    /// An operation that straddles the boundary (touches qubits both inside
+    /// and outside the subset) is dropped, or is an error when `strict` is
+    /// set.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::InvalidConfig`] if `qubits` has an out-of-range
+    /// or repeated index, or (with `strict`) names the first straddling
+    /// operation.
+    pub fn subcircuit_on_qubits(&self, qubits: &[usize], strict: bool) -> Result<Circuit> {
+        let mut index = vec![None; self.num_qubits];
+        for (i, &q) in qubits.iter().enumerate() {
+            match index.get_mut(q) {
+                Some(slot @ None) => *slot = Some(i),
+                _ => {
+                    return Err(QRustError::InvalidConfig(format!(
+                        "{qubits:?} is not a set of distinct qubits of a {}-qubit circuit",
+                        self.num_qubits
+                    )))
                 }
-                Operation::Barrier { qubits } => {
-                    let m = qubits
-                        .iter()
-                        .filter_map(|&q| qd.get(q).copied())
-                        .max()
-                        .unwrap_or(0);
-                    for &q in qubits {
-                        if let Some(slot) = qd.get_mut(q) {
-                            *slot = m;
-                        }
-                    }
+            }
+        }
+
+        let slot = |q: usize| index.get(q).copied().flatten();
+
+        let mut sub = Circuit::new(qubits.len(), self.num_cbits);
+        sub.custom_gates = self.custom_gates.clone();
+        sub.cregs = self.cregs.clone();
+        for (i, op) in self.operations.iter().enumerate() {
+            if let Operation::Barrier { qubits: bq } = op {
+                let inside: Vec<usize> = bq.iter().filter_map(|&q| slot(q)).collect();
+                if !inside.is_empty() {
+                    sub.add_op(Operation::Barrier { qubits: inside });
                 }
-                Operation::Conditional { op, .. } => {
-                    let qubits = op.qubits();
-                    let m = qubits
-                        .iter()
-                        .filter_map(|&q| qd.get(q).copied())
-                        .max()
-                        .unwrap_or(0);
-                    let new_d = m + 1;
-                    for &q in qubits {
-                        if let Some(slot) = qd.get_mut(q) {
-                            *slot = new_d;
-                        }
-                    }
+                continue;
+            }
+            let touched = op.qubits();
+            let inside = touched.iter().filter(|&&q| slot(q).is_some()).count();
+            if inside == 0 {
+                continue;
+            }
+            if inside < touched.len() {
+                if strict {
+                    return Err(QRustError::InvalidConfig(format!(
+                        "op #{i} ({op}) straddles the qubit subset {qubits:?}"
+                    )));
                 }
+                continue;
             }
+            let mut op = op.clone();
+            op.map_qubits(&mut |q| slot(q).unwrap_or(q));
+            sub.add_op(op);
         }
-        qd.into_iter().max().unwrap_or(0)
+        Ok(sub)
     }
 
-    /// Returns the number of [`Operation::Gate`] operations.
-    pub fn gate_count(&self) -> usize {
-        self.operations
-            .iter()
-            .filter(|op| matches!(op, Operation::Gate { .. }))
-            .count()
+    /// Returns a copy of the circuit with every custom gate recursively
+    /// inlined from `defs`, e.g. after building or editing a circuit
+    /// programmatically. See
+    /// [`crate::transpiler::decomposition::try_unroll_custom_gates_with`].
+    ///
+    /// # Errors
+    /// Returns [`QRustError::Decomposition`] if a custom gate (at any
+    /// nesting depth) has no definition in `defs`.
+    #[cfg(feature = "std")]
+    pub fn decompose_all_custom(&self, defs: &GateRegistry) -> Result<Circuit> {
+        crate::transpiler::decomposition::try_unroll_custom_gates_with(self, defs)
     }
 
-    /// Counts occurrences of each gate kind in the circuit.
+    /// Returns a smaller, equivalent circuit: the one-call cleanup for when
+    /// assembling a [`crate::transpiler::pass::PassManager`] is overkill.
+    /// Identity rotations are dropped, adjacent inverses cancelled,
+    /// rotations merged and single-qubit `U` runs fused, to a fixed point.
+    /// No backend or target basis is involved.
+    #[cfg(feature = "std")]
+    pub fn optimize(&self) -> Circuit {
+        crate::transpiler::optimize_to_fixed_point(self)
+    }
+
+    /// Serializes the circuit to compact JSON.
     ///
-    /// - [`Operation::Gate`] is tallied under its `GateType`.
-    /// - [`Operation::Barrier`] is tallied under [`GateType::Barrier`].
-    /// - [`Operation::Conditional`] is recursed into.
-    /// - Measurement/Reset are not counted.
-    pub fn count_ops(&self) -> HashMap<GateType, usize> {
-        let mut counts: HashMap<GateType, usize> = HashMap::new();
-        fn walk(op: &Operation, counts: &mut HashMap<GateType, usize>) {
+    /// The custom-gate registry is not serialized; unroll custom gates first
+    /// if the JSON must be self-contained.
+    #[cfg(feature = "serde-ir")]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self)
+            .map_err(|e| QRustError::Internal(format!("cannot serialize circuit: {e}")))
+    }
+
+    /// Like [`Circuit::to_json`], but indented for humans.
+    #[cfg(feature = "serde-ir")]
+    pub fn to_json_pretty(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| QRustError::Internal(format!("cannot serialize circuit: {e}")))
+    }
+
+    /// Deserializes a circuit produced by [`Circuit::to_json`].
+    ///
+    /// # Errors
+    /// Returns [`QRustError::ParseError`] on malformed JSON.
+    #[cfg(feature = "serde-ir")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| QRustError::ParseError(format!("invalid circuit JSON: {e}")))
+    }
+
+    /// Returns the depth (critical-path length). Barriers bump each affected
+    /// wire's depth tracker to the max so subsequent ops line up after the
+    /// barrier, but barriers themselves don't add depth.
+    pub fn depth(&self) -> usize {
+        self.weighted_depth(|_| 1) as usize
+    }
+
+    /// Indices of one longest dependency chain, in execution order: its
+    /// length is [`Circuit::depth`] and consecutive entries share a qubit
+    /// (or are linked through a barrier). Barriers are never listed. Among
+    /// equally long chains, each step prefers the lowest operation index.
+    pub fn critical_path(&self) -> Vec<usize> {
+        // Per qubit: depth so far and the operation that reached it.
+        let mut front: Vec<(usize, Option<usize>)> = vec![(0, None); self.num_qubits];
+        let mut pred: Vec<Option<usize>> = vec![None; self.operations.len()];
+        let mut depth = vec![0usize; self.operations.len()];
+        for (i, op) in self.operations.iter().enumerate() {
+            let qubits = op.qubits();
+            let (d, p) = qubits
+                .iter()
+                .filter_map(|&q| front.get(q).copied())
+                .min_by_key(|&(d, p)| (core::cmp::Reverse(d), p))
+                .unwrap_or((0, None));
+            let reached = if op.is_barrier() {
+                (d, p)
+            } else {
+                pred[i] = p;
+                depth[i] = d + 1;
+                (d + 1, Some(i))
+            };
+            for &q in qubits {
+                if let Some(slot) = front.get_mut(q) {
+                    *slot = reached;
+                }
+            }
+        }
+
+        let mut last = depth
+            .iter()
+            .enumerate()
+            .filter(|&(_, &d)| d > 0)
+            .min_by_key(|&(i, &d)| (core::cmp::Reverse(d), i))
+            .map(|(i, _)| i);
+        let mut path = Vec::new();
+        while let Some(i) = last {
+            path.push(i);
+            last = pred[i];
+        }
+        path.reverse();
+        path
+    }
+
+    /// ASAP schedule length where each non-barrier operation takes
+    /// `duration(op)` on all of its qubits. A conditional is scheduled on
+    /// its inner operation's qubits; barriers align their wires but take no
+    /// time. [`Circuit::depth`] is the unit-duration case.
+    pub fn weighted_depth(&self, duration: impl Fn(&Operation) -> u64) -> u64 {
+        self.schedule(duration)
+            .into_iter()
+            .map(|(_, end)| end)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// ASAP `(start, end)` interval of every operation, in operation order,
+    /// under the same rules as [`Circuit::weighted_depth`].
+    pub fn schedule(&self, duration: impl Fn(&Operation) -> u64) -> Vec<(u64, u64)> {
+        let mut qd = vec![0u64; self.num_qubits];
+        let mut intervals = Vec::with_capacity(self.operations.len());
+        for op in &self.operations {
+            let qubits = op.qubits();
+            let start = qubits
+                .iter()
+                .filter_map(|&q| qd.get(q).copied())
+                .max()
+                .unwrap_or(0);
+            let end = match op {
+                Operation::Barrier { .. } => start,
+                other => start + duration(other),
+            };
+            for &q in qubits {
+                if let Some(slot) = qd.get_mut(q) {
+                    *slot = end;
+                }
+            }
+            intervals.push((start, end));
+        }
+        intervals
+    }
+
+    /// Groups operation indices into ASAP layers of unit duration: each
+    /// layer's operations act on disjoint wires and depend only on earlier
+    /// layers. Unlike [`Circuit::schedule`], classical bits are wires too —
+    /// a measurement writes its cbit and a conditional reads its register —
+    /// so a conditional always lands after the measurements it tests.
+    /// Barriers align their qubits but are not listed.
+    pub fn layers(&self) -> Vec<Vec<usize>> {
+        let mut qd = vec![0usize; self.num_qubits];
+        let mut cd = vec![0usize; self.num_cbits];
+        let mut layers: Vec<Vec<usize>> = Vec::new();
+        for (i, op) in self.operations.iter().enumerate() {
+            let mut cbits = Vec::new();
+            match op {
+                Operation::Measure { cbit, .. } => cbits.push(*cbit),
+                Operation::Conditional {
+                    condition,
+                    op: inner,
+                } => {
+                    // An unknown register is read as every bit.
+                    cbits.extend(self.creg_bits(&condition.creg).unwrap_or(0..self.num_cbits));
+                    if let Operation::Measure { cbit, .. } = **inner {
+                        cbits.push(cbit);
+                    }
+                }
+                _ => {}
+            }
+            let qubits = op.qubits();
+            let start = qubits
+                .iter()
+                .filter_map(|&q| qd.get(q))
+                .chain(cbits.iter().filter_map(|&c| cd.get(c)))
+                .copied()
+                .max()
+                .unwrap_or(0);
+            let end = if op.is_barrier() {
+                start
+            } else {
+                if layers.len() <= start {
+                    layers.resize_with(start + 1, Vec::new);
+                }
+                layers[start].push(i);
+                start + 1
+            };
+            for &q in qubits {
+                if let Some(slot) = qd.get_mut(q) {
+                    *slot = end;
+                }
+            }
+            for c in cbits {
+                if let Some(slot) = cd.get_mut(c) {
+                    *slot = end;
+                }
+            }
+        }
+        layers
+    }
+
+    /// Classical bits of register `creg`. A circuit without declared
+    /// registers has one implicit register `c` covering every bit; any
+    /// other unknown name is `None`.
+    pub(crate) fn creg_bits(&self, creg: &str) -> Option<Range<usize>> {
+        match self.cregs.iter().find(|r| r.name == creg) {
+            Some(r) => Some(r.start..r.start + r.size),
+            None if self.cregs.is_empty() && creg == "c" => Some(0..self.num_cbits),
+            None => None,
+        }
+    }
+
+    /// Indices of the operations running at time `t` in the ASAP schedule,
+    /// i.e. whose interval `[start, end)` contains `t`.
+    ///
+    /// `durations` is keyed like [`crate::backend::Backend::gate_durations`]; operations
+    /// missing from it take 1 time unit, and barriers take none (so are
+    /// never active).
+    pub fn operations_active_at(&self, t: u64, durations: &HashMap<String, u64>) -> Vec<usize> {
+        self.schedule(|op| {
+            op.duration_key()
+                .map_or(0, |k| durations.get(k).copied().unwrap_or(1))
+        })
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, (start, end))| start <= t && t < end)
+        .map(|(i, _)| i)
+        .collect()
+    }
+
+    /// Splits the circuit into independent blocks: groups of qubits that
+    /// never interact, directly or through shared classical bits.
+    ///
+    /// Each entry is `(qubits, sub_circuit)`, where `qubits` lists the
+    /// original qubit indices in ascending order and `sub_circuit` acts on
+    /// them renumbered `0..qubits.len()`. Cbit indices and the classical
+    /// width are kept, so results can be recombined bitwise. Barriers are
+    /// restricted to each block's qubits. Idle qubits form their own blocks.
+    /// Blocks are ordered by their lowest qubit.
+    #[cfg(feature = "std")]
+    pub fn split_parallel_blocks(&self) -> Vec<(Vec<usize>, Circuit)> {
+        let nq = self.num_qubits;
+        // Nodes 0..nq are qubits, nq.. are cbits.
+        let mut uf = petgraph::unionfind::UnionFind::<usize>::new(nq + self.num_cbits);
+        for op in &self.operations {
+            if op.is_barrier() {
+                continue;
+            }
+            let mut cbits = Vec::new();
+            match op {
+                Operation::Measure { cbit, .. } => cbits.push(*cbit),
+                Operation::Conditional {
+                    condition,
+                    op: inner,
+                } => {
+                    // An unknown register is read as every bit.
+                    cbits.extend(self.creg_bits(&condition.creg).unwrap_or(0..self.num_cbits));
+                    if let Operation::Measure { cbit, .. } = **inner {
+                        cbits.push(cbit);
+                    }
+                }
+                _ => {}
+            }
+            let nodes: Vec<usize> = op
+                .qubits()
+                .iter()
+                .copied()
+                .filter(|&q| q < nq)
+                .chain(
+                    cbits
+                        .into_iter()
+                        .filter(|&c| c < self.num_cbits)
+                        .map(|c| nq + c),
+                )
+                .collect();
+            for w in nodes.windows(2) {
+                uf.union(w[0], w[1]);
+            }
+        }
+
+        let mut blocks: Vec<Vec<usize>> = Vec::new();
+        let mut block_of_root: HashMap<usize, usize> = HashMap::new();
+        for q in 0..nq {
+            let root = uf.find(q);
+            let b = *block_of_root.entry(root).or_insert_with(|| {
+                blocks.push(Vec::new());
+                blocks.len() - 1
+            });
+            blocks[b].push(q);
+        }
+
+        let mut local = vec![0usize; nq];
+        let mut subs: Vec<Circuit> = blocks
+            .iter()
+            .map(|qs| {
+                for (i, &q) in qs.iter().enumerate() {
+                    local[q] = i;
+                }
+                let mut sub = Circuit::new(qs.len(), self.num_cbits);
+                sub.custom_gates = self.custom_gates.clone();
+                sub.cregs = self.cregs.clone();
+                sub
+            })
+            .collect();
+        for op in &self.operations {
+            if let Operation::Barrier { qubits } = op {
+                let mut per_block: Vec<Vec<usize>> = vec![Vec::new(); blocks.len()];
+                for &q in qubits.iter().filter(|&&q| q < nq) {
+                    per_block[block_of_root[&uf.find(q)]].push(local[q]);
+                }
+                for (b, qubits) in per_block.into_iter().enumerate() {
+                    if !qubits.is_empty() {
+                        subs[b].add_op(Operation::Barrier { qubits });
+                    }
+                }
+                continue;
+            }
+            let Some(&first) = op.qubits().first() else {
+                continue;
+            };
+            if first >= nq {
+                continue;
+            }
+            let mut mapped = op.clone();
+            mapped.map_qubits(&mut |q| local[q]);
+            subs[block_of_root[&uf.find(first)]].add_op(mapped);
+        }
+        blocks.into_iter().zip(subs).collect()
+    }
+
+    /// Checks that this circuit implements the same unitary as `other`, up
+    /// to global phase and within `tol` per matrix entry — the usual check
+    /// when testing a custom pass. See [`crate::verify::verify_against`].
+    ///
+    /// # Errors
+    /// Returns [`QRustError::NotEquivalent`] naming the largest amplitude
+    /// difference when the circuits diverge.
+    #[cfg(feature = "std")]
+    pub fn verify_against(&self, other: &Circuit, tol: f64) -> Result<()> {
+        crate::verify::verify_against(self, other, tol)
+    }
+
+    /// Renders the circuit as text, one row per qubit and one column per
+    /// operation.
+    ///
+    /// Every qubit an operation touches shows the operation's label (e.g.
+    /// `CX`, `RZ(0.50)`, `M:c0`, or a custom gate's name), and rows crossed
+    /// by a multi-qubit operation show `│`, so a gate reads as a box
+    /// spanning its qubits.
+    pub fn draw(&self) -> String {
+        let mut rows: Vec<String> = (0..self.num_qubits).map(|q| format!("q{q}: ")).collect();
+        let label_width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0);
+        for row in &mut rows {
+            while row.chars().count() < label_width {
+                row.insert(0, ' ');
+            }
+        }
+        for op in &self.operations {
+            let label = draw_label(op);
+            let width = label.chars().count() + 2;
+            let qubits = op.qubits();
+            let span = qubits.iter().min().zip(qubits.iter().max());
+            for (q, row) in rows.iter_mut().enumerate() {
+                let cell = if qubits.contains(&q) {
+                    label.clone()
+                } else if span.is_some_and(|(&lo, &hi)| lo < q && q < hi) {
+                    "│".to_string()
+                } else {
+                    String::new()
+                };
+                let pad = width - cell.chars().count();
+                row.push_str(&"─".repeat(pad / 2));
+                row.push_str(&cell);
+                row.push_str(&"─".repeat(pad - pad / 2));
+            }
+        }
+        let mut out = String::new();
+        for row in rows {
+            out.push_str(&row);
+            out.push_str("─\n");
+        }
+        out
+    }
+
+    /// Estimated hardware cost of running this circuit on `backend`; see
+    /// [`crate::backend::Backend::circuit_cost`].
+    #[cfg(feature = "std")]
+    pub fn hardware_cost(&self, backend: &crate::backend::Backend) -> f64 {
+        backend.circuit_cost(self)
+    }
+
+    /// Returns the number of [`Operation::Gate`] operations.
+    pub fn gate_count(&self) -> usize {
+        self.operations
+            .iter()
+            .filter(|op| matches!(op, Operation::Gate { .. }))
+            .count()
+    }
+
+    /// Counts occurrences of each gate kind in the circuit.
+    ///
+    /// - [`Operation::Gate`] is tallied under its `GateType`.
+    /// - [`Operation::Barrier`] is tallied under [`GateType::Barrier`].
+    /// - [`Operation::Conditional`] is recursed into.
+    /// - Measurement/Reset are not counted.
+    pub fn count_ops(&self) -> HashMap<GateType, usize> {
+        let mut counts: HashMap<GateType, usize> = HashMap::new();
+        fn walk(op: &Operation, counts: &mut HashMap<GateType, usize>) {
             match op {
                 Operation::Gate { name, .. } => {
                     *counts.entry(name.clone()).or_insert(0) += 1;
@@ -177,6 +1414,300 @@ impl Circuit {
         }
         counts
     }
+
+    /// Histogram of the circuit's operations keyed by QASM mnemonic: gates
+    /// under their [`GateType::to_qasm_name`] (`"h"`, `"cx"`, or the custom
+    /// gate's name), and measurements, resets and barriers under
+    /// `"measure"`, `"reset"` and `"barrier"`. Conditionals are counted
+    /// under their inner operation.
+    pub fn gate_counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for op in &self.operations {
+            let mut op = op;
+            while let Operation::Conditional { op: inner, .. } = op {
+                op = inner;
+            }
+            let key = match op {
+                Operation::Gate { name, .. } => name.to_qasm_name(),
+                Operation::Measure { .. } => "measure",
+                Operation::Reset { .. } => "reset",
+                Operation::Barrier { .. } => "barrier",
+                Operation::Conditional { .. } => unreachable!("unwrapped above"),
+            };
+            *counts.entry(key.to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Number of gates (conditional ones included) acting on exactly two
+    /// qubits — the usual cost metric on hardware where two-qubit gates
+    /// dominate the error budget.
+    pub fn num_two_qubit_gates(&self) -> usize {
+        self.operations
+            .iter()
+            .filter(|&op| {
+                let mut op = op;
+                while let Operation::Conditional { op: inner, .. } = op {
+                    op = inner;
+                }
+                matches!(op, Operation::Gate { qubits, .. } if qubits.len() == 2)
+            })
+            .count()
+    }
+
+    /// Renames every `from` gate (including conditional ones) to `to`, e.g.
+    /// to turn all CX into CZ for a quick experiment, and returns how many
+    /// were replaced. Parametric gates match on their type whatever their
+    /// angles; custom gates match by name.
+    ///
+    /// Qubits and parameters are kept as they are, so `to` should take the
+    /// same number of each; the circuit is generally not equivalent
+    /// afterwards.
+    pub fn replace_gate_type(&mut self, from: &GateType, to: GateType) -> usize {
+        fn walk(op: &mut Operation, from: &GateType, to: &GateType) -> usize {
+            match op {
+                Operation::Gate { name, .. } if name == from => {
+                    *name = to.clone();
+                    1
+                }
+                Operation::Conditional { op, .. } => walk(op, from, to),
+                _ => 0,
+            }
+        }
+        self.operations
+            .iter_mut()
+            .map(|op| walk(op, from, &to))
+            .sum()
+    }
+
+    /// Returns the number of explicit [`GateType::SWAP`] gates, including
+    /// conditional ones — e.g. the overhead left by routing.
+    pub fn count_swaps(&self) -> usize {
+        self.count_gates_where(|name, _| *name == GateType::SWAP)
+    }
+
+    /// Returns the number of gates acting on two or more qubits, including
+    /// conditional ones. Barriers are not gates and are not counted.
+    pub fn count_entangling_gates(&self) -> usize {
+        self.count_gates_where(|_, qubits| qubits.len() >= 2)
+    }
+
+    /// Returns the sorted qubit set of every gate acting on two or more
+    /// qubits (including conditional ones), one entry per operation in
+    /// circuit order. Unlike a pairwise interaction graph this keeps CCX and
+    /// other multi-qubit gates intact, so layout heuristics can weigh them.
+    /// Barriers are not interactions and are skipped.
+    pub fn interaction_hyperedges(&self) -> Vec<Vec<usize>> {
+        fn gate_qubits(op: &Operation) -> Option<&[usize]> {
+            match op {
+                Operation::Gate { qubits, .. } => Some(qubits),
+                Operation::Conditional { op, .. } => gate_qubits(op),
+                _ => None,
+            }
+        }
+        self.operations
+            .iter()
+            .filter_map(gate_qubits)
+            .filter(|qubits| qubits.len() >= 2)
+            .map(|qubits| {
+                let mut edge = qubits.to_vec();
+                edge.sort_unstable();
+                edge
+            })
+            .collect()
+    }
+
+    /// Compares two circuits operation by operation, ignoring barriers and
+    /// treating gate parameters within `1e-9` of each other as equal (see
+    /// [`Operation::approx_eq`]). Widths must match; names, registers and
+    /// global phase are not compared.
+    pub fn structurally_eq(&self, other: &Circuit) -> bool {
+        const EPS: f64 = 1e-9;
+        if self.num_qubits != other.num_qubits || self.num_cbits != other.num_cbits {
+            return false;
+        }
+        let mut lhs = self.operations.iter().filter(|op| !op.is_barrier());
+        let mut rhs = other.operations.iter().filter(|op| !op.is_barrier());
+        loop {
+            match (lhs.next(), rhs.next()) {
+                (None, None) => return true,
+                (Some(a), Some(b)) if a.approx_eq(b, EPS) => {}
+                _ => return false,
+            }
+        }
+    }
+
+    /// A hash of the circuit's width and operations that is stable across
+    /// runs and platforms, for memoizing transpilation or simulation results.
+    ///
+    /// Barriers are skipped and gate parameters are quantized to `1e-9`, so
+    /// circuits that are [`Circuit::structurally_eq`] hash equally (barring
+    /// parameters that straddle a quantization boundary). Names, registers
+    /// and global phase are not hashed.
+    pub fn content_hash(&self) -> u64 {
+        // FNV-1a: unlike `DefaultHasher`, its output is fixed by definition.
+        struct Fnv(u64);
+        impl Fnv {
+            fn bytes(&mut self, bytes: &[u8]) {
+                for &b in bytes {
+                    self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+                }
+            }
+            fn word(&mut self, w: u64) {
+                self.bytes(&w.to_le_bytes());
+            }
+            fn op(&mut self, op: &Operation) {
+                const QUANTUM: f64 = 1e-9;
+                match op {
+                    Operation::Gate {
+                        name,
+                        qubits,
+                        params,
+                    } => {
+                        self.word(0);
+                        self.word(matches!(name, GateType::Custom(_)) as u64);
+                        self.word(name.to_qasm_name().len() as u64);
+                        self.bytes(name.to_qasm_name().as_bytes());
+                        self.word(qubits.len() as u64);
+                        qubits.iter().for_each(|&q| self.word(q as u64));
+                        self.word(params.len() as u64);
+                        for p in params {
+                            self.word((p / QUANTUM).round() as i64 as u64);
+                        }
+                    }
+                    Operation::Measure { qubit, cbit } => {
+                        self.word(1);
+                        self.word(*qubit as u64);
+                        self.word(*cbit as u64);
+                    }
+                    Operation::Reset { qubit } => {
+                        self.word(2);
+                        self.word(*qubit as u64);
+                    }
+                    Operation::Barrier { .. } => {}
+                    Operation::Conditional { condition, op } => {
+                        self.word(3);
+                        self.word(condition.creg.len() as u64);
+                        self.bytes(condition.creg.as_bytes());
+                        self.word(condition.value);
+                        self.op(op);
+                    }
+                }
+            }
+        }
+
+        let mut h = Fnv(0xcbf2_9ce4_8422_2325);
+        h.word(self.num_qubits as u64);
+        h.word(self.num_cbits as u64);
+        for op in &self.operations {
+            h.op(op);
+        }
+        h.0
+    }
+
+    fn count_gates_where(&self, pred: impl Fn(&GateType, &[usize]) -> bool) -> usize {
+        fn hit(op: &Operation, pred: &dyn Fn(&GateType, &[usize]) -> bool) -> bool {
+            match op {
+                Operation::Gate { name, qubits, .. } => pred(name, qubits),
+                Operation::Conditional { op, .. } => hit(op, pred),
+                _ => false,
+            }
+        }
+        self.operations.iter().filter(|op| hit(op, &pred)).count()
+    }
+}
+
+/// One way an operation can be ill-formed, as found by
+/// [`Circuit::op_violations`].
+enum OpViolation {
+    Qubit {
+        index: usize,
+        size: usize,
+    },
+    Cbit {
+        index: usize,
+        size: usize,
+    },
+    RepeatedQubit {
+        gate: String,
+        qubit: usize,
+    },
+    Arity {
+        gate: String,
+        arity: usize,
+        applied: usize,
+    },
+}
+
+impl fmt::Display for OpViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpViolation::Qubit { index, size } => {
+                write!(f, "qubit {index} is out of range for {size} qubits")
+            }
+            OpViolation::Cbit { index, size } => {
+                write!(f, "cbit {index} is out of range for {size} cbits")
+            }
+            OpViolation::RepeatedQubit { gate, qubit } => {
+                write!(f, "{gate} repeats qubit {qubit}")
+            }
+            OpViolation::Arity {
+                gate,
+                arity,
+                applied,
+            } => write!(
+                f,
+                "{gate} acts on {arity} qubit(s) but is applied to {applied}"
+            ),
+        }
+    }
+}
+
+impl From<OpViolation> for QRustError {
+    fn from(violation: OpViolation) -> Self {
+        match violation {
+            OpViolation::Qubit { index, size } => QRustError::IndexOutOfBounds {
+                name: "q".into(),
+                index,
+                size,
+            },
+            OpViolation::Cbit { index, size } => QRustError::IndexOutOfBounds {
+                name: "c".into(),
+                index,
+                size,
+            },
+            other => QRustError::InvalidOperation(other.to_string()),
+        }
+    }
+}
+
+/// Label drawn for `op` by [`Circuit::draw`].
+fn draw_label(op: &Operation) -> String {
+    match op {
+        Operation::Gate { name, params, .. } => {
+            let name = match name {
+                GateType::Custom(n) => n.clone(),
+                std => std.to_qasm_name().to_uppercase(),
+            };
+            if params.is_empty() {
+                name
+            } else {
+                let params: Vec<String> = params.iter().map(|p| format!("{p:.2}")).collect();
+                format!("{name}({})", params.join(","))
+            }
+        }
+        Operation::Measure { cbit, .. } => format!("M:c{cbit}"),
+        Operation::Reset { .. } => "|0>".to_string(),
+        Operation::Barrier { .. } => "░".to_string(),
+        Operation::Conditional { condition, op } => {
+            format!(
+                "{} if {}=={}",
+                draw_label(op),
+                condition.creg,
+                condition.value
+            )
+        }
+    }
 }
 
 impl fmt::Display for Circuit {
@@ -202,93 +1733,1366 @@ mod tests {
     use crate::ir::gates::GateType;
 
     #[test]
-    fn test_circuit_creation() {
-        let c = Circuit::new(2, 2);
-        assert_eq!(c.num_qubits, 2);
-        assert_eq!(c.num_cbits, 2);
-        assert!(c.operations.is_empty());
-    }
+    fn test_subcircuit_on_qubits() {
+        let mut c = Circuit::new(3, 1);
+        for (name, qubits) in [
+            (GateType::H, vec![1]),
+            (GateType::CX, vec![1, 0]),
+            (GateType::X, vec![2]),
+            (GateType::CX, vec![0, 2]),
+        ] {
+            c.add_op(Operation::Gate {
+                name,
+                qubits,
+                params: vec![],
+            });
+        }
+        c.add_op(Operation::Barrier {
+            qubits: vec![0, 1, 2],
+        });
+        c.add_op(Operation::Measure { qubit: 1, cbit: 0 });
 
-    #[test]
-    fn test_add_op() {
-        let mut c = Circuit::new(1, 0);
-        let op = Operation::Gate {
-            name: GateType::H,
-            qubits: vec![0],
-            params: vec![],
-        };
-        c.add_op(op.clone());
-        assert_eq!(c.operations, vec![op]);
+        let sub = c.subcircuit_on_qubits(&[1, 0], false).unwrap();
+        assert_eq!(sub.num_qubits, 2);
+        assert_eq!(sub.num_cbits, 1);
+        assert_eq!(
+            sub.operations,
+            vec![
+                Operation::Gate {
+                    name: GateType::H,
+                    qubits: vec![0],
+                    params: vec![],
+                },
+                Operation::Gate {
+                    name: GateType::CX,
+                    qubits: vec![0, 1],
+                    params: vec![],
+                },
+                Operation::Barrier { qubits: vec![1, 0] },
+                Operation::Measure { qubit: 0, cbit: 0 },
+            ]
+        );
+
+        let err = c.subcircuit_on_qubits(&[0, 1], true).unwrap_err();
+        assert!(err.to_string().contains("op #3"), "{err}");
+        assert!(c.subcircuit_on_qubits(&[0, 0], false).is_err());
+        assert!(c.subcircuit_on_qubits(&[3], false).is_err());
     }
 
     #[test]
-    fn test_depth_simple() {
-        let mut c = Circuit::new(2, 0);
+    fn test_to_qasm3_bell() {
+        let mut c = Circuit::new(2, 2);
         c.add_op(Operation::Gate {
             name: GateType::H,
             qubits: vec![0],
             params: vec![],
         });
-        c.add_op(Operation::Gate {
-            name: GateType::H,
-            qubits: vec![1],
-            params: vec![],
-        });
         c.add_op(Operation::Gate {
             name: GateType::CX,
             qubits: vec![0, 1],
             params: vec![],
         });
-        assert_eq!(c.depth(), 2);
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Measure { qubit: 1, cbit: 1 });
+        c.add_op(Operation::Conditional {
+            condition: crate::ir::ClassicalCondition {
+                creg: "c".into(),
+                value: 1,
+            },
+            op: Box::new(Operation::Gate {
+                name: GateType::U,
+                qubits: vec![1],
+                params: vec![0.5, 0.0, 0.0],
+            }),
+        });
+
+        let qasm = c.to_qasm3();
+        assert!(qasm.starts_with("OPENQASM 3.0;\n"));
+        assert!(qasm.contains("qubit[2] q;\n"));
+        assert!(qasm.contains("bit[2] c;\n"));
+        assert!(qasm.contains("h q[0];\ncx q[0], q[1];\n"));
+        assert!(qasm.contains("c[0] = measure q[0];\nc[1] = measure q[1];\n"));
+        assert!(qasm.contains("if (c == 1) { U(0.5, 0, 0) q[1]; }"));
+        assert!(!qasm.contains("->"));
     }
 
     #[test]
-    fn test_count_ops() {
+    fn test_replace_gate_type() {
+        let mut c = Circuit::new(2, 1);
+        for (name, qubits) in [
+            (GateType::H, vec![0]),
+            (GateType::CX, vec![0, 1]),
+            (GateType::H, vec![1]),
+        ] {
+            c.add_op(Operation::Gate {
+                name,
+                qubits,
+                params: vec![],
+            });
+        }
+        c.add_op(Operation::Conditional {
+            condition: crate::ir::ClassicalCondition {
+                creg: "c".into(),
+                value: 1,
+            },
+            op: Box::new(Operation::Gate {
+                name: GateType::H,
+                qubits: vec![0],
+                params: vec![],
+            }),
+        });
+
+        assert_eq!(c.replace_gate_type(&GateType::H, GateType::X), 3);
+        let counts = c.count_ops();
+        assert_eq!(counts.get(&GateType::H), None);
+        assert_eq!(counts.get(&GateType::X), Some(&3));
+        assert_eq!(counts.get(&GateType::CX), Some(&1));
+        assert_eq!(c.replace_gate_type(&GateType::H, GateType::X), 0);
+    }
+
+    #[test]
+    fn test_optimize_reduces_redundant_circuit() {
         let mut c = Circuit::new(2, 0);
-        c.add_op(Operation::Gate {
+        for (name, qubits, params) in [
+            (GateType::H, vec![0], vec![]),
+            (GateType::H, vec![0], vec![]),
+            (GateType::RZ, vec![1], vec![0.1]),
+            (GateType::RZ, vec![1], vec![0.2]),
+            (GateType::CX, vec![0, 1], vec![]),
+        ] {
+            c.add_op(Operation::Gate {
+                name,
+                qubits,
+                params,
+            });
+        }
+        let opt = c.optimize();
+        assert!(opt.gate_count() < c.gate_count(), "{opt}");
+        assert_eq!(opt.gate_count(), 2, "{opt}");
+        assert!(c.verify_against(&opt, 1e-9).is_ok());
+        assert_eq!(opt.optimize().operations, opt.operations);
+    }
+
+    #[test]
+    fn test_layers_respect_classical_wires() {
+        let mut c = Circuit::new(2, 1);
+        for _ in 0..2 {
+            c.add_op(Operation::Gate {
+                name: GateType::H,
+                qubits: vec![0],
+                params: vec![],
+            });
+        }
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Barrier { qubits: vec![0, 1] });
+        c.add_op(Operation::Conditional {
+            condition: crate::ir::ClassicalCondition {
+                creg: "c".into(),
+                value: 1,
+            },
+            op: Box::new(Operation::Gate {
+                name: GateType::X,
+                qubits: vec![1],
+                params: vec![],
+            }),
+        });
+        assert_eq!(c.layers(), vec![vec![0], vec![1], vec![2], vec![4]]);
+
+        let mut bell = Circuit::new(2, 0);
+        bell.add_op(Operation::Gate {
             name: GateType::H,
             qubits: vec![0],
             params: vec![],
         });
-        c.add_op(Operation::Gate {
-            name: GateType::H,
+        bell.add_op(Operation::Gate {
+            name: GateType::X,
             qubits: vec![1],
             params: vec![],
         });
-        c.add_op(Operation::Gate {
+        bell.add_op(Operation::Gate {
             name: GateType::CX,
             qubits: vec![0, 1],
             params: vec![],
         });
-        c.add_op(Operation::Barrier { qubits: vec![0, 1] });
-        let counts = c.count_ops();
-        assert_eq!(counts.get(&GateType::H), Some(&2));
-        assert_eq!(counts.get(&GateType::CX), Some(&1));
-        assert_eq!(counts.get(&GateType::Barrier), Some(&1));
-    }
-
-    #[test]
-    fn test_validation_with_measurements() {
-        let mut c = Circuit::new(1, 1);
-        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
-        assert!(c.validate().is_empty());
+        assert_eq!(bell.layers(), vec![vec![0, 1], vec![2]]);
+        assert_eq!(bell.layers().len(), bell.depth());
     }
 
     #[test]
-    fn test_to_qasm_with_barrier() {
-        let mut c = Circuit::new(2, 0);
+    fn test_content_hash() {
+        let mut c = Circuit::new(2, 1);
         c.add_op(Operation::Gate {
-            name: GateType::H,
+            name: GateType::RZ,
             qubits: vec![0],
-            params: vec![],
+            params: vec![0.25],
         });
-        c.add_op(Operation::Barrier { qubits: vec![0, 1] });
         c.add_op(Operation::Gate {
             name: GateType::CX,
             qubits: vec![0, 1],
             params: vec![],
         });
-        let qasm = c.to_qasm(None);
-        assert!(qasm.contains("barrier q[0], q[1];"));
+        c.add_op(Operation::Measure { qubit: 1, cbit: 0 });
+        let h = c.content_hash();
+        assert_eq!(c.clone().content_hash(), h);
+
+        let mut with_barrier = c.clone();
+        with_barrier
+            .operations
+            .insert(1, Operation::Barrier { qubits: vec![0, 1] });
+        assert_eq!(with_barrier.content_hash(), h);
+
+        let mut nudged = c.clone();
+        nudged.operations[0] = Operation::Gate {
+            name: GateType::RZ,
+            qubits: vec![0],
+            params: vec![0.25 + 1e-13],
+        };
+        assert_eq!(nudged.content_hash(), h);
+
+        let mut changed = c.clone();
+        changed.operations[0] = Operation::Gate {
+            name: GateType::RZ,
+            qubits: vec![0],
+            params: vec![0.25 + 1e-6],
+        };
+        assert_ne!(changed.content_hash(), h);
+        assert_ne!(
+            Circuit::new(3, 1).content_hash(),
+            Circuit::new(2, 1).content_hash()
+        );
+    }
+
+    #[test]
+    fn test_interaction_hyperedges() {
+        let mut c = Circuit::new(4, 0);
+        for (name, qubits) in [
+            (GateType::CX, vec![1, 0]),
+            (GateType::H, vec![3]),
+            (GateType::CCX, vec![2, 0, 1]),
+            (GateType::CX, vec![2, 3]),
+        ] {
+            c.add_op(Operation::Gate {
+                name,
+                qubits,
+                params: vec![],
+            });
+        }
+        c.add_op(Operation::Barrier {
+            qubits: vec![0, 1, 2, 3],
+        });
+        assert_eq!(
+            c.interaction_hyperedges(),
+            vec![vec![0, 1], vec![0, 1, 2], vec![2, 3]]
+        );
+    }
+
+    #[test]
+    fn test_structurally_eq_ignores_barriers() {
+        let mut a = Circuit::new(2, 0);
+        a.add_op(Operation::Gate {
+            name: GateType::RZ,
+            qubits: vec![0],
+            params: vec![0.5],
+        });
+        a.add_op(Operation::Barrier { qubits: vec![0, 1] });
+        a.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+
+        let mut b = Circuit::new(2, 0);
+        b.add_op(Operation::Gate {
+            name: GateType::RZ,
+            qubits: vec![0],
+            params: vec![0.5 + 1e-12],
+        });
+        b.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        assert!(a.structurally_eq(&b));
+        assert!(b.structurally_eq(&a));
+
+        let mut c = b.clone();
+        c.operations[1] = Operation::Gate {
+            name: GateType::CZ,
+            qubits: vec![0, 1],
+            params: vec![],
+        };
+        assert!(!a.structurally_eq(&c));
+
+        let mut d = b.clone();
+        d.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![1],
+            params: vec![],
+        });
+        assert!(!a.structurally_eq(&d));
+    }
+
+    #[test]
+    fn test_circuit_creation() {
+        let c = Circuit::new(2, 2);
+        assert_eq!(c.num_qubits, 2);
+        assert_eq!(c.num_cbits, 2);
+        assert!(c.operations.is_empty());
+    }
+
+    #[test]
+    fn test_add_op() {
+        let mut c = Circuit::new(1, 0);
+        let op = Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        };
+        c.add_op(op.clone());
+        assert_eq!(c.operations, vec![op]);
+    }
+
+    #[test]
+    fn test_append_valid() {
+        let mut c = Circuit::new(2, 1);
+        c.append(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        })
+        .unwrap();
+        c.append(Operation::Measure { qubit: 1, cbit: 0 }).unwrap();
+        assert_eq!(c.operations.len(), 2);
+    }
+
+    #[test]
+    fn test_append_out_of_range_errors() {
+        let mut c = Circuit::new(2, 1);
+        let err = c
+            .append(Operation::Gate {
+                name: GateType::CX,
+                qubits: vec![0, 2],
+                params: vec![],
+            })
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            QRustError::IndexOutOfBounds {
+                index: 2,
+                size: 2,
+                ..
+            }
+        ));
+        assert!(c.append(Operation::Measure { qubit: 0, cbit: 1 }).is_err());
+        let err = c
+            .append(Operation::Gate {
+                name: GateType::CX,
+                qubits: vec![1, 1],
+                params: vec![],
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "invalid operation: cx repeats qubit 1");
+        assert!(matches!(
+            c.append(Operation::Gate {
+                name: GateType::CX,
+                qubits: vec![0],
+                params: vec![],
+            }),
+            Err(QRustError::InvalidOperation(_))
+        ));
+        assert!(c.operations.is_empty());
+    }
+
+    #[test]
+    fn test_apply_cyclic_permutation() {
+        let mut c = Circuit::new(3, 1);
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        c.add_op(Operation::Barrier { qubits: vec![1, 2] });
+        c.add_op(Operation::Measure { qubit: 2, cbit: 0 });
+        c.apply_permutation(&[1, 2, 0]).unwrap();
+        assert_eq!(c.operations[0].qubits(), &[1, 2]);
+        assert_eq!(c.operations[1].qubits(), &[2, 0]);
+        assert_eq!(c.operations[2], Operation::Measure { qubit: 0, cbit: 0 });
+
+        assert!(c.apply_permutation(&[0, 0, 1]).is_err());
+        assert!(c.apply_permutation(&[0, 1]).is_err());
+        assert_eq!(c.operations[0].qubits(), &[1, 2]);
+    }
+
+    #[test]
+    fn test_decompose_all_custom_nested() {
+        use crate::ir::ast::{Expr, ParsedStatement};
+        let mut defs = GateRegistry::new();
+        // gate inner(theta) a { rz(theta) a; h a; }
+        defs.register(
+            "inner".into(),
+            vec!["theta".into()],
+            vec!["a".into()],
+            vec![
+                ParsedStatement::Gate(
+                    "rz".into(),
+                    vec![("a".into(), None)],
+                    vec![Expr::Var("theta".into())],
+                ),
+                ParsedStatement::Gate("h".into(), vec![("a".into(), None)], vec![]),
+            ],
+        );
+        // gate outer a, b { inner(pi) b; cx a, b; }
+        defs.register(
+            "outer".into(),
+            vec![],
+            vec!["a".into(), "b".into()],
+            vec![
+                ParsedStatement::Gate(
+                    "inner".into(),
+                    vec![("b".into(), None)],
+                    vec![Expr::Var("pi".into())],
+                ),
+                ParsedStatement::Gate(
+                    "cx".into(),
+                    vec![("a".into(), None), ("b".into(), None)],
+                    vec![],
+                ),
+            ],
+        );
+        let mut c = Circuit::new(2, 0);
+        c.add_op(Operation::Gate {
+            name: GateType::Custom("outer".into()),
+            qubits: vec![1, 0],
+            params: vec![],
+        });
+
+        let flat = c.decompose_all_custom(&defs).unwrap();
+        assert_eq!(
+            flat.operations,
+            vec![
+                Operation::Gate {
+                    name: GateType::RZ,
+                    qubits: vec![0],
+                    params: vec![std::f64::consts::PI],
+                },
+                Operation::Gate {
+                    name: GateType::H,
+                    qubits: vec![0],
+                    params: vec![],
+                },
+                Operation::Gate {
+                    name: GateType::CX,
+                    qubits: vec![1, 0],
+                    params: vec![],
+                },
+            ]
+        );
+
+        let err = c.decompose_all_custom(&GateRegistry::new()).unwrap_err();
+        assert!(matches!(err, QRustError::Decomposition(_)));
+    }
+
+    #[test]
+    fn test_find_cx_ops() {
+        let mut c = Circuit::new(2, 0);
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![1, 0],
+            params: vec![],
+        });
+        let found = c.find(|op| {
+            matches!(
+                op,
+                Operation::Gate {
+                    name: GateType::CX,
+                    ..
+                }
+            )
+        });
+        assert_eq!(found, vec![0, 2]);
+        assert!(c
+            .find(|op| matches!(op, Operation::Reset { .. }))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_depth_simple() {
+        let mut c = Circuit::new(2, 0);
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![1],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        assert_eq!(c.depth(), 2);
+    }
+
+    #[test]
+    fn test_depth_of_log_depth_ghz() {
+        // Doubling the entangled set each layer: 8 gates, depth 4.
+        let mut qasm = String::from("OPENQASM 2.0;\nqreg q[8];\ncreg c[8];\nh q[0];\n");
+        for (ctrl, tgt) in [(0, 1), (0, 2), (1, 3), (0, 4), (1, 5), (2, 6), (3, 7)] {
+            qasm.push_str(&format!("cx q[{ctrl}], q[{tgt}];\n"));
+        }
+        let ghz = crate::parser::parse_qasm(&qasm).unwrap();
+        assert_eq!(ghz.operations.len(), 8);
+        assert_eq!(ghz.depth(), 4);
+
+        // The barrier lines up all measurements in one extra layer, even on
+        // qubits that were idle since layer 2.
+        qasm.push_str("barrier q;\nmeasure q -> c;\n");
+        let measured = crate::parser::parse_qasm(&qasm).unwrap();
+        assert_eq!(measured.depth(), 5);
+    }
+
+    #[test]
+    fn test_count_ops() {
+        let mut c = Circuit::new(2, 0);
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![1],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        c.add_op(Operation::Barrier { qubits: vec![0, 1] });
+        let counts = c.count_ops();
+        assert_eq!(counts.get(&GateType::H), Some(&2));
+        assert_eq!(counts.get(&GateType::CX), Some(&1));
+        assert_eq!(counts.get(&GateType::Barrier), Some(&1));
+    }
+
+    #[test]
+    fn test_gate_counts_and_two_qubit_gates() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[3];\ncreg c[3];\ngate bell a, b { h a; cx a, b; }\n\
+             h q[0];\nh q[1];\ncx q[0], q[1];\nbell q[1], q[2];\nccx q[0], q[1], q[2];\n\
+             barrier q[0], q[1];\nreset q[2];\nmeasure q -> c;\nif(c==1) cz q[0], q[2];\n",
+        )
+        .unwrap();
+        let counts = c.gate_counts();
+        let expected: HashMap<String, usize> = [
+            ("h", 2),
+            ("cx", 1),
+            ("bell", 1),
+            ("ccx", 1),
+            ("cz", 1),
+            ("barrier", 1),
+            ("reset", 1),
+            ("measure", 3),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        assert_eq!(counts, expected);
+        // cx, bell and the conditional cz; not the barrier or the ccx.
+        assert_eq!(c.num_two_qubit_gates(), 3);
+    }
+
+    #[test]
+    fn test_operations_active_at() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[3];\ncreg c[1];\n\
+             cx q[0],q[1];\nh q[2];\nbarrier q;\nmeasure q[2] -> c[0];\n",
+        )
+        .unwrap();
+        let durations = HashMap::from([
+            ("cx".to_string(), 300),
+            ("h".to_string(), 50),
+            ("measure".to_string(), 1000),
+        ]);
+        // cx runs over [0, 300) and h over [0, 50): both active at t = 20.
+        assert_eq!(c.operations_active_at(20, &durations), vec![0, 1]);
+        assert_eq!(c.operations_active_at(100, &durations), vec![0]);
+        // The barrier aligns the measurement after the cx.
+        assert_eq!(c.operations_active_at(300, &durations), vec![3]);
+        assert!(c.operations_active_at(1300, &durations).is_empty());
+    }
+
+    #[test]
+    fn test_hardware_cost_prefers_fewer_cx() {
+        // Two equivalent circuits: a SWAP written as three CX, and the same
+        // three CX followed by a cancelling CX pair.
+        let three = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[2];\ncx q[0],q[1];\ncx q[1],q[0];\ncx q[0],q[1];\n",
+        )
+        .unwrap();
+        let mut five = three.clone();
+        for _ in 0..2 {
+            five.add_op(Operation::Gate {
+                name: GateType::CX,
+                qubits: vec![0, 1],
+                params: vec![],
+            });
+        }
+        let mut backend = crate::backend::Backend::linear(2);
+        assert_eq!(three.hardware_cost(&backend), 3.0);
+        assert!(three.hardware_cost(&backend) < five.hardware_cost(&backend));
+
+        backend.set_edge_error(0, 1, 0.01).unwrap();
+        let expected = -3.0 * 0.99f64.ln();
+        assert!((three.hardware_cost(&backend) - expected).abs() < 1e-12);
+        assert!(three.hardware_cost(&backend) < five.hardware_cost(&backend));
+    }
+
+    #[test]
+    fn test_split_parallel_blocks() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[3];\ncreg c[3];\n\
+             h q[0];\nx q[2];\nbarrier q;\nmeasure q[0] -> c[0];\nmeasure q[2] -> c[2];\n",
+        )
+        .unwrap();
+        let blocks = c.split_parallel_blocks();
+        let qubits: Vec<&[usize]> = blocks.iter().map(|(q, _)| q.as_slice()).collect();
+        assert_eq!(qubits, [&[0][..], &[1], &[2]]);
+
+        let (_, third) = &blocks[2];
+        assert_eq!(third.num_qubits, 1);
+        assert_eq!(third.num_cbits, 3);
+        assert_eq!(
+            third.operations,
+            vec![
+                Operation::Gate {
+                    name: GateType::X,
+                    qubits: vec![0],
+                    params: vec![],
+                },
+                Operation::Barrier { qubits: vec![0] },
+                Operation::Measure { qubit: 0, cbit: 2 },
+            ]
+        );
+        // The idle qubit only sees its slice of the barrier.
+        assert_eq!(blocks[1].1.operations.len(), 1);
+
+        // An entangling gate joins the two experiments.
+        let mut joined = c.clone();
+        joined.operations.push(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 2],
+            params: vec![],
+        });
+        let blocks = joined.split_parallel_blocks();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].0, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_count_swaps_and_entangling_gates() {
+        let mut c = Circuit::new(3, 1);
+        for (name, qubits) in [
+            (GateType::H, vec![0]),
+            (GateType::CX, vec![0, 1]),
+            (GateType::SWAP, vec![1, 2]),
+            (GateType::CCX, vec![0, 1, 2]),
+            (GateType::SWAP, vec![0, 1]),
+        ] {
+            c.add_op(Operation::Gate {
+                name,
+                qubits,
+                params: vec![],
+            });
+        }
+        c.add_op(Operation::Barrier {
+            qubits: vec![0, 1, 2],
+        });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Conditional {
+            condition: crate::ir::ClassicalCondition {
+                creg: "c".into(),
+                value: 1,
+            },
+            op: Box::new(Operation::Gate {
+                name: GateType::CX,
+                qubits: vec![1, 2],
+                params: vec![],
+            }),
+        });
+        assert_eq!(c.count_swaps(), 2);
+        assert_eq!(c.count_entangling_gates(), 5);
+    }
+
+    #[test]
+    fn test_validation_with_measurements() {
+        let mut c = Circuit::new(1, 1);
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        assert!(c.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validation_flags_double_measure() {
+        let mut c = Circuit::new(1, 2);
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Barrier { qubits: vec![0] });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 1 });
+        let warnings = c.validate();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("qubit 0 is measured twice"));
+    }
+
+    #[test]
+    fn test_validation_reports_repeated_measure_once() {
+        let mut c = Circuit::new(1, 1);
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        let warnings = c.validate();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("qubit 0 is measured twice"));
+    }
+
+    #[test]
+    fn test_validation_conditional_read_clears_cbit_write() {
+        let mut c = Circuit::new(2, 1);
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Conditional {
+            condition: crate::ir::ClassicalCondition {
+                creg: "c".into(),
+                value: 1,
+            },
+            op: Box::new(Operation::Gate {
+                name: GateType::X,
+                qubits: vec![1],
+                params: vec![],
+            }),
+        });
+        c.add_op(Operation::Measure { qubit: 1, cbit: 0 });
+        assert!(c.validate().is_empty(), "{:?}", c.validate());
+    }
+
+    #[test]
+    fn test_validation_reset_suppresses_double_measure() {
+        let mut c = Circuit::new(1, 2);
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Reset { qubit: 0 });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 1 });
+        assert!(c.validate().is_empty());
+    }
+
+    #[test]
+    fn test_check_reports_every_violation() {
+        let mut c = Circuit::new(2, 1);
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        assert_eq!(c.check(), Ok(()));
+
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 2],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![1],
+            params: vec![],
+        });
+        c.add_op(Operation::Measure { qubit: 1, cbit: 3 });
+        c.register_custom_gate("pair".into(), vec![], vec!["a".into(), "b".into()], vec![]);
+        c.add_op(Operation::Gate {
+            name: GateType::Custom("pair".into()),
+            qubits: vec![0],
+            params: vec![],
+        });
+        assert_eq!(
+            c.check().unwrap_err(),
+            vec![
+                "op #1: qubit 2 is out of range for 2 qubits",
+                "op #2: cx acts on 2 qubit(s) but is applied to 1",
+                "op #3: cbit 3 is out of range for 1 cbits",
+                "op #4: pair acts on 2 qubit(s) but is applied to 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_rejects_repeated_qubits() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[2];\ncx q[0], q[0];\n\
+             ccx q[0], q[1], q[0];\nswap q[1], q[0];\n",
+        )
+        .unwrap();
+        assert_eq!(
+            c.check().unwrap_err(),
+            vec!["op #0: cx repeats qubit 0", "op #1: ccx repeats qubit 0"]
+        );
+    }
+
+    #[test]
+    fn test_gate_at_and_len() {
+        let mut c = Circuit::new(1, 0);
+        assert!(c.is_empty());
+        assert_eq!(c.gate_at(0), None);
+        c.add_op(Operation::Reset { qubit: 0 });
+        assert_eq!((c.len(), c.is_empty()), (1, false));
+        assert_eq!(c.gate_at(0), Some(&Operation::Reset { qubit: 0 }));
+        assert_eq!(c.gate_at(1), None);
+    }
+
+    #[test]
+    fn test_qubits_used_and_idle_qubits() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[5];\ncreg c[1];\nh q[0];\n\
+             barrier q;\nif(c==1) x q[2];\n",
+        )
+        .unwrap();
+        assert_eq!(c.qubits_used(), HashSet::from([0, 2]));
+        assert_eq!(c.idle_qubits(), vec![1, 3, 4]);
+        assert_eq!(Circuit::new(2, 0).idle_qubits(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_reindex_cbits() {
+        let mut c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[2];\ncreg c[4];\nh q[0];\nmeasure q[0] -> c[3];\n\
+             measure q[1] -> c[0];\n",
+        )
+        .unwrap();
+        let map = c.reindex_cbits();
+        assert_eq!(map, vec![Some(0), None, None, Some(1)]);
+        assert_eq!(c.num_cbits, 2);
+        assert_eq!(c.cregs, vec![Register::new("c", 0, 2)]);
+        assert_eq!(c.operations[1], Operation::Measure { qubit: 0, cbit: 1 });
+        assert_eq!(c.operations[2], Operation::Measure { qubit: 1, cbit: 0 });
+
+        // A register tested by a conditional keeps all its bits; an unused
+        // register disappears.
+        let mut c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[1];\ncreg unused[2];\ncreg flag[2];\ncreg out[1];\n\
+             if(flag==2) x q[0];\nmeasure q[0] -> out[0];\n",
+        )
+        .unwrap();
+        assert_eq!(
+            c.reindex_cbits(),
+            vec![None, None, Some(0), Some(1), Some(2)]
+        );
+        assert_eq!(
+            c.cregs,
+            vec![Register::new("flag", 0, 2), Register::new("out", 2, 1)]
+        );
+        assert_eq!(c.operations[1], Operation::Measure { qubit: 0, cbit: 2 });
+        crate::parser::parse_qasm(&c.to_qasm(None)).unwrap();
+    }
+
+    #[test]
+    fn test_compose_adds_global_phase() {
+        let mut fragment = Circuit::new(1, 0);
+        fragment.global_phase = 0.25;
+        fragment.add_op(Operation::Gate {
+            name: GateType::RZ,
+            qubits: vec![0],
+            params: vec![0.3],
+        });
+        let mut c = Circuit::new(2, 0);
+        c.global_phase = 0.5;
+        c.compose(&fragment, &[1], &[]).unwrap();
+        assert_eq!(c.global_phase, 0.75);
+
+        c.compose(&fragment.inverse().unwrap(), &[1], &[]).unwrap();
+        assert!((c.global_phase - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_compose() {
+        let mut bell = Circuit::new(2, 2);
+        bell.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        bell.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        bell.add_op(Operation::Measure { qubit: 1, cbit: 1 });
+
+        // Disjoint: onto qubits 2 and 3 of a fresh 4-qubit circuit.
+        let mut c = Circuit::new(4, 4);
+        c.compose(&bell, &[2, 3], &[2, 3]).unwrap();
+        assert_eq!(
+            c.operations[1],
+            Operation::Gate {
+                name: GateType::CX,
+                qubits: vec![2, 3],
+                params: vec![],
+            }
+        );
+        assert_eq!(c.operations[2], Operation::Measure { qubit: 3, cbit: 3 });
+
+        // Overlapping and flipped: qubits 3, 2 again after the first copy.
+        c.compose(&bell, &[3, 2], &[0, 1]).unwrap();
+        assert_eq!(c.operations.len(), 6);
+        assert_eq!(
+            c.operations[4],
+            Operation::Gate {
+                name: GateType::CX,
+                qubits: vec![3, 2],
+                params: vec![],
+            }
+        );
+        assert_eq!(c.operations[5], Operation::Measure { qubit: 2, cbit: 1 });
+
+        let before = c.clone();
+        for (qmap, cmap) in [
+            (&[0][..], &[0, 1][..]),
+            (&[0, 4][..], &[0, 1][..]),
+            (&[1, 1][..], &[0, 1][..]),
+            (&[0, 1][..], &[0][..]),
+        ] {
+            assert!(c.compose(&bell, qmap, cmap).is_err(), "{qmap:?} {cmap:?}");
+        }
+        assert_eq!(c, before);
+    }
+
+    #[test]
+    fn test_inverse_undoes_circuit() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[3];\nh q[0];\ns q[1];\n\
+             cx q[0], q[1];\nbarrier q;\nu(0.3, 0.7, -1.1) q[2];\nt q[2];\nrzz(0.4) q[1], q[2];\n",
+        )
+        .unwrap();
+        let inverse = c.inverse().unwrap();
+        assert_eq!(inverse.operations[0].to_qasm(), "rzz(-0.4) q[1], q[2];");
+        assert_eq!(
+            inverse.operations[3],
+            Operation::Barrier {
+                qubits: vec![0, 1, 2]
+            }
+        );
+        assert_eq!(inverse.inverse().unwrap(), c);
+
+        let mut round_trip = c.clone();
+        round_trip.operations.extend(inverse.operations);
+        round_trip
+            .verify_against(&Circuit::new(3, 0), 1e-9)
+            .unwrap();
+
+        let mut measured = c.clone();
+        measured.num_cbits = 1;
+        measured.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        let err = measured.inverse().unwrap_err();
+        assert!(matches!(err, QRustError::Unsupported(_)), "{err:?}");
+        assert!(err.to_string().contains("operation 7"), "{err}");
+    }
+
+    #[test]
+    fn test_inverse_expands_gates_without_builtin_adjoint() {
+        for body in [
+            "p(0.3) q[0];\ncu1(0.5) q[0], q[1];\nsx q[1];\n",
+            "csx q[0], q[1];\niswap q[1], q[0];\nif (c == 1) csx q[1], q[0];\n",
+        ] {
+            let c = crate::parser::parse_qasm(&format!(
+                "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[2];\ncreg c[1];\n{body}"
+            ))
+            .unwrap();
+            let inverse = c.inverse().unwrap();
+            let mut round_trip = c.clone();
+            round_trip.operations.extend(inverse.operations.clone());
+            round_trip
+                .verify_against(&Circuit::new(2, 1), 1e-9)
+                .unwrap_or_else(|e| panic!("{body}: {e}"));
+
+            let reparsed = crate::parser::parse_qasm(&inverse.to_qasm(None)).unwrap();
+            reparsed
+                .verify_against(&inverse, 1e-9)
+                .unwrap_or_else(|e| panic!("{body}: {e}"));
+        }
+
+        let mut c = Circuit::new(1, 0);
+        let i = num_complex::Complex::new(0.0, 1.0);
+        let one = num_complex::Complex::new(1.0, 0.0);
+        let zero = num_complex::Complex::new(0.0, 0.0);
+        c.register_custom_unitary(
+            "sq".into(),
+            nalgebra::DMatrix::from_row_slice(2, 2, &[one, zero, zero, i]),
+        )
+        .unwrap();
+        c.add_op(Operation::Gate {
+            name: GateType::Custom("sq".into()),
+            qubits: vec![0],
+            params: vec![],
+        });
+        let inverse = c.inverse().unwrap();
+        assert_eq!(
+            inverse.operations[0],
+            Operation::Gate {
+                name: GateType::Custom("sq_dg".into()),
+                qubits: vec![0],
+                params: vec![],
+            }
+        );
+        let mut round_trip = c.clone();
+        round_trip.custom_gates = inverse.custom_gates.clone();
+        round_trip.operations.extend(inverse.operations);
+        round_trip
+            .verify_against(&Circuit::new(1, 0), 1e-9)
+            .unwrap();
+
+        let mut opaque = Circuit::new(1, 0);
+        opaque.add_op(Operation::Gate {
+            name: GateType::Custom("mystery".into()),
+            qubits: vec![0],
+            params: vec![],
+        });
+        assert!(matches!(
+            opaque.inverse(),
+            Err(QRustError::Decomposition(_))
+        ));
+    }
+
+    #[test]
+    fn test_measure_all() {
+        let mut c = Circuit::new(3, 0);
+        c.measure_all();
+        assert_eq!(c.num_cbits, 3);
+        assert_eq!(c.cregs, vec![Register::new("c", 0, 3)]);
+        let measures: Vec<_> = c
+            .operations
+            .iter()
+            .map(|op| match op {
+                Operation::Measure { qubit, cbit } => (*qubit, *cbit),
+                other => panic!("expected a measurement, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(measures, vec![(0, 0), (1, 1), (2, 2)]);
+
+        let mut c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[3];\ncreg flag[1];\nmeasure q[0] -> flag[0];\n",
+        )
+        .unwrap();
+        c.measure_all();
+        assert_eq!(c.num_cbits, 3);
+        assert_eq!(
+            c.cregs,
+            vec![Register::new("flag", 0, 1), Register::new("meas", 1, 2)]
+        );
+        assert!(c.to_qasm(None).contains("measure q[2] -> meas[1];"));
+    }
+
+    #[test]
+    fn test_measure_in_x_and_y_bases() {
+        let mut c = Circuit::new(2, 2);
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        c.measure_x(0, 0).unwrap();
+        assert_eq!(c.operations[1].to_qasm(), "h q[0];");
+        assert_eq!(c.operations[2], Operation::Measure { qubit: 0, cbit: 0 });
+
+        // |+i> = S H |0> on qubit 1.
+        for gate in [GateType::H, GateType::S] {
+            c.add_op(Operation::Gate {
+                name: gate,
+                qubits: vec![1],
+                params: vec![],
+            });
+        }
+        c.measure_y(1, 1).unwrap();
+        let tail: Vec<String> = c.operations[5..].iter().map(|op| op.to_qasm()).collect();
+        assert_eq!(tail, ["sdg q[1];", "h q[1];", "measure q[1] -> c[1];"]);
+
+        let counts = crate::simulator::sample(&c, 200, 7).unwrap();
+        assert_eq!(counts.get("00"), Some(&200), "{counts:?}");
+
+        let before = c.operations.len();
+        assert!(c.measure_x(0, 5).is_err());
+        assert!(c.measure_y(9, 0).is_err());
+        assert_eq!(c.operations.len(), before);
+    }
+
+    #[test]
+    fn test_is_primitive() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[2];\ncreg c[2];\nh q[0];\ncx q[0], q[1];\nbarrier q;\n\
+             reset q[1];\nmeasure q -> c;\nif(c==1) rz(0.3) q[1];\n",
+        )
+        .unwrap();
+        assert!(!c.is_primitive());
+
+        let lowered = crate::transpiler::decomposition::try_decompose_basis(&c).unwrap();
+        assert!(lowered.is_primitive());
+        assert_eq!(lowered.gate_counts().get("reset"), Some(&1));
+
+        let mut with_h = lowered.clone();
+        with_h.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        assert!(!with_h.is_primitive());
+        assert!(Circuit::new(1, 0).is_primitive());
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[3];\ncreg c[1];\nx q[2];\nh q[0];\ncx q[0], q[1];\n\
+             z q[2];\nmeasure q[1] -> c[0];\n",
+        )
+        .unwrap();
+        assert_eq!(c.critical_path(), vec![1, 2, 4]);
+        assert_eq!(c.critical_path().len(), c.depth());
+
+        // A barrier links otherwise independent wires but is not listed.
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[2];\nh q[0];\nh q[0];\nbarrier q;\nx q[1];\n",
+        )
+        .unwrap();
+        assert_eq!(c.critical_path(), vec![0, 1, 3]);
+        assert!(Circuit::new(2, 0).critical_path().is_empty());
+    }
+
+    #[test]
+    fn test_to_qasm_with_barrier() {
+        let mut c = Circuit::new(2, 0);
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        c.add_op(Operation::Barrier { qubits: vec![0, 1] });
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        let qasm = c.to_qasm(None);
+        assert!(qasm.contains("barrier q[0], q[1];"));
+    }
+
+    #[test]
+    fn test_to_qasm_renders_pi_fractions() {
+        use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+        let mut c = Circuit::new(1, 0);
+        for params in [
+            vec![FRAC_PI_2, 0.0, PI],
+            vec![-FRAC_PI_2, 3.0 * PI / 4.0, 2.0 * PI],
+            vec![FRAC_PI_4 + 1e-3, 0.5, -PI / 3.0],
+        ] {
+            c.add_op(Operation::Gate {
+                name: GateType::U,
+                qubits: vec![0],
+                params,
+            });
+        }
+        let qasm = c.to_qasm(None);
+        assert!(qasm.contains("u(pi/2, 0, pi) q[0];"), "{qasm}");
+        assert!(qasm.contains("u(-pi/2, 3*pi/4, 2*pi) q[0];"), "{qasm}");
+        // Not a π fraction: plain decimals.
+        assert!(
+            qasm.contains("u(0.7863981633974483, 0.5, -pi/3) q[0];"),
+            "{qasm}"
+        );
+
+        let reparsed = crate::parser::parse_qasm(&qasm).unwrap();
+        assert_eq!(reparsed.operations, c.operations);
+    }
+
+    #[test]
+    fn test_barrier_round_trips_through_qasm() {
+        let src = "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg a[2];\nqreg b[2];\n\
+                   h a[0];\nbarrier a, b[1];\nbarrier b[0], a[1];\ncx a[0], b[0];\n";
+        let c = crate::parser::parse_qasm(src).unwrap();
+        let qasm = c.to_qasm(None);
+        assert!(qasm.contains("barrier a, b[1];"), "{qasm}");
+        assert!(qasm.contains("barrier b[0], a[1];"), "{qasm}");
+
+        let barriers = |c: &Circuit| -> Vec<Vec<usize>> {
+            c.operations
+                .iter()
+                .filter(|op| op.is_barrier())
+                .map(|op| op.qubits().to_vec())
+                .collect()
+        };
+        let reparsed = crate::parser::parse_qasm(&qasm).unwrap();
+        assert_eq!(barriers(&reparsed), vec![vec![0, 1, 3], vec![2, 1]]);
+        assert_eq!(barriers(&reparsed), barriers(&c));
+    }
+
+    #[test]
+    fn test_to_qasm_preserves_global_phase() {
+        let mut c = Circuit::new(1, 0);
+        c.global_phase = std::f64::consts::FRAC_PI_4;
+        let reparsed = crate::parser::parse_qasm(&c.to_qasm(None)).unwrap();
+        assert!((reparsed.global_phase - c.global_phase).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_merge_registers_flattens_export() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            include "qelib1.inc";
+            qreg a[2];
+            qreg b[1];
+            creg m[3];
+            cx a[1], b[0];
+            if(m==1) x a[0];
+        "#;
+        let mut c = crate::parser::parse_qasm(qasm).unwrap();
+        let named = c.to_qasm(None);
+        assert!(named.contains("qreg a[2];\nqreg b[1];"), "{named}");
+        assert!(named.contains("cx a[1], b[0];"), "{named}");
+
+        c.merge_registers();
+        assert_eq!(c.qregs, vec![Register::new("q", 0, 3)]);
+        let merged = c.to_qasm(None);
+        assert!(merged.contains("qreg q[3];"), "{merged}");
+        assert!(!merged.contains("qreg a"), "{merged}");
+        assert!(merged.contains("cx q[1], q[2];"), "{merged}");
+        assert!(merged.contains("if(c==1) x q[0];"), "{merged}");
+
+        let reparsed = crate::parser::parse_qasm(&merged).unwrap();
+        assert_eq!(reparsed.operations, c.operations);
+    }
+
+    #[cfg(feature = "serde-ir")]
+    #[test]
+    fn test_json_round_trip() {
+        let mut c = crate::parser::parse_qasm(
+            r#"
+            OPENQASM 2.0;
+            qreg q[2];
+            creg c[2];
+            h q[0];
+            rz(pi/4) q[1];
+            cx q[0], q[1];
+            if(c==1) x q[0];
+            measure q -> c;
+            "#,
+        )
+        .unwrap();
+        c.global_phase = 0.5;
+        let compact = c.to_json().unwrap();
+        assert!(!compact.contains('\n'));
+        assert_eq!(Circuit::from_json(&compact).unwrap(), c);
+        let pretty = c.to_json_pretty().unwrap();
+        assert!(pretty.contains('\n'));
+        assert_eq!(Circuit::from_json(&pretty).unwrap(), c);
+        assert!(matches!(
+            Circuit::from_json("{not json"),
+            Err(QRustError::ParseError(_))
+        ));
+    }
+
+    #[cfg(feature = "serde-ir")]
+    #[test]
+    fn test_json_round_trip_every_operation() {
+        use GateType::*;
+        let mut c = Circuit::new(3, 1);
+        for name in [
+            H,
+            X,
+            Y,
+            Z,
+            S,
+            Sdg,
+            T,
+            Tdg,
+            ID,
+            RX,
+            RY,
+            RZ,
+            U,
+            CX,
+            CY,
+            CZ,
+            CH,
+            CSX,
+            CRX,
+            CRY,
+            CRZ,
+            RXX,
+            RYY,
+            RZZ,
+            SWAP,
+            CCX,
+            ECR,
+            ISwap,
+            Custom("foo".into()),
+        ] {
+            let qubits: Vec<usize> = (0..name.num_qubits().unwrap_or(1)).collect();
+            let params = match name {
+                U => vec![0.1, 0.2, 0.3],
+                ref g if g.is_parametric() => vec![1.57],
+                _ => vec![],
+            };
+            c.add_op(Operation::Gate {
+                name,
+                qubits,
+                params,
+            });
+        }
+        c.add_op(Operation::Measure { qubit: 2, cbit: 0 });
+        c.add_op(Operation::Reset { qubit: 1 });
+        c.add_op(Operation::Barrier {
+            qubits: vec![0, 1, 2],
+        });
+
+        let json = c.to_json().unwrap();
+        assert!(
+            json.contains(r#"{"Gate":{"name":"RX","qubits":[0],"params":[1.57]}}"#),
+            "{json}"
+        );
+        assert!(json.contains(r#""name":{"Custom":"foo"}"#), "{json}");
+        assert_eq!(Circuit::from_json(&json).unwrap(), c);
+    }
+
+    #[test]
+    fn test_to_qasm_primitive_bell_round_trip() {
+        let mut c = Circuit::new(2, 2);
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Measure { qubit: 1, cbit: 1 });
+
+        let qasm = c.to_qasm_primitive().unwrap();
+        assert!(!qasm.contains("include"), "{qasm}");
+        assert!(qasm.contains("CX q[0], q[1];"), "{qasm}");
+        assert!(qasm.contains("U("), "{qasm}");
+
+        let reparsed = crate::parser::parse_qasm(&qasm).unwrap();
+        assert_eq!(reparsed.num_qubits, 2);
+        assert_eq!(reparsed.num_cbits, 2);
+        let counts = reparsed.count_ops();
+        assert_eq!(counts.get(&GateType::U), Some(&1));
+        assert_eq!(counts.get(&GateType::CX), Some(&1));
     }
 }
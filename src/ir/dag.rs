@@ -0,0 +1,9 @@
+//! DAG form of a [`crate::ir::Circuit`], where nodes are operations and edges
+//! follow the qubit and classical-bit wires in program order.
+//!
+//! The implementation lives in [`crate::transpiler::dag`], next to the
+//! passes that rewrite it; this module exposes it alongside the rest of the
+//! IR as [`CircuitDag`]. Convert with [`CircuitDag::from_circuit`] and
+//! [`CircuitDag::to_circuit`].
+
+pub use crate::transpiler::dag::{DAGCircuit as CircuitDag, DAGNode, Wire, WireType};
@@ -1,9 +1,14 @@
 //! Unified gate definitions.
 
 use crate::ir::{CommutationSignature, GateType, Operation, PauliBasis};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
 use nalgebra::DMatrix;
 use num_complex::Complex;
-use std::f64::consts::PI;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 type C = Complex<f64>;
 
@@ -119,44 +124,15 @@ pub trait GateDefinition {
 
 impl GateDefinition for GateType {
     fn num_qubits(&self) -> usize {
-        match self {
-            GateType::H
-            | GateType::X
-            | GateType::Y
-            | GateType::Z
-            | GateType::S
-            | GateType::Sdg
-            | GateType::T
-            | GateType::Tdg
-            | GateType::ID
-            | GateType::RX
-            | GateType::RY
-            | GateType::RZ
-            | GateType::U => 1,
-
-            GateType::CX
-            | GateType::CZ
-            | GateType::CY
-            | GateType::CH
-            | GateType::CSX
-            | GateType::CRX
-            | GateType::CRY
-            | GateType::CRZ
-            | GateType::RXX
-            | GateType::RYY
-            | GateType::RZZ
-            | GateType::ECR
-            | GateType::ISwap
-            | GateType::SWAP => 2,
-
-            GateType::CCX => 3,
-            GateType::Barrier => 0,
+        match GateType::num_qubits(self) {
+            Some(n) => n,
+            None if *self == GateType::Barrier => 0,
             // Custom gates always report arity 1 because GateType carries only
             // the gate name, not the qubit count. For actual arity, inspect
             // Operation::Gate { qubits, .. }.len() at the call site. Custom
             // gates should be unrolled via try_unroll_custom_gates / the
             // registry before any unitary computation is attempted.
-            GateType::Custom(_) => 1,
+            None => 1,
         }
     }
 
@@ -177,7 +153,7 @@ impl GateDefinition for GateType {
             GateType::Custom(_) => DMatrix::<C>::identity(2, 2),
             GateType::Barrier => DMatrix::<C>::identity(1, 1),
             other => {
-                let n = other.num_qubits();
+                let n = GateDefinition::num_qubits(other);
                 let qubits: Vec<usize> = (0..n).collect();
                 match other.decompose(&qubits, params) {
                     Some(ops) => compose_basis_ops(&ops, n),
@@ -234,12 +210,7 @@ impl GateDefinition for GateType {
                 ops.push(u_gate(qubits[0], [0.0, 0.0, phi]));
             }
 
-            GateType::SWAP => {
-                let (a, b) = (qubits[0], qubits[1]);
-                ops.push(cx_gate(a, b));
-                ops.push(cx_gate(b, a));
-                ops.push(cx_gate(a, b));
-            }
+            GateType::SWAP => return self.decompose_default(qubits),
 
             GateType::CCX => {
                 let (a, b, t) = (qubits[0], qubits[1], qubits[2]);
@@ -420,10 +391,83 @@ impl GateDefinition for GateType {
     }
 }
 
+// Lives here rather than in `gates.rs`, which is kept free of `nalgebra`.
+impl GateType {
+    /// The gate's unitary as rows of complex entries — 2×2, 4×4, or 8×8 for
+    /// `CCX` — in the same qubit ordering as
+    /// [`crate::simulator::circuit_to_unitary`]. `params` are the gate's
+    /// angles (`θ` for the rotations, `θ, φ, λ` for `U`).
+    ///
+    /// Returns `None` for [`GateType::Custom`] and [`GateType::Barrier`],
+    /// which have no fixed matrix, and when `params` has the wrong length.
+    pub fn matrix(&self, params: &[f64]) -> Option<Vec<Vec<C>>> {
+        let expected = match self {
+            GateType::Custom(_) | GateType::Barrier => return None,
+            GateType::U => 3,
+            g if g.is_parametric() => 1,
+            _ => 0,
+        };
+        if params.len() != expected {
+            return None;
+        }
+        let m = self.unitary(params);
+        Some(
+            m.row_iter()
+                .map(|row| row.iter().copied().collect())
+                .collect(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_matrix() {
+        let mul = |a: &[Vec<C>], b: &[Vec<C>]| -> Vec<Vec<C>> {
+            (0..a.len())
+                .map(|i| {
+                    (0..a.len())
+                        .map(|j| (0..a.len()).map(|k| a[i][k] * b[k][j]).sum())
+                        .collect()
+                })
+                .collect()
+        };
+        let close = |a: &[Vec<C>], b: &[Vec<C>]| {
+            a.iter()
+                .flatten()
+                .zip(b.iter().flatten())
+                .all(|(x, y)| (x - y).norm() < 1e-12)
+        };
+
+        let h = GateType::H.matrix(&[]).unwrap();
+        let id = GateType::ID.matrix(&[]).unwrap();
+        assert!(close(&mul(&h, &h), &id));
+
+        // RX(π) = -iX.
+        let rx = GateType::RX.matrix(&[PI]).unwrap();
+        let minus_i_x: Vec<Vec<C>> = GateType::X
+            .matrix(&[])
+            .unwrap()
+            .into_iter()
+            .map(|row| row.into_iter().map(|z| z * c(0.0, -1.0)).collect())
+            .collect();
+        assert!(close(&rx, &minus_i_x));
+
+        let u = GateType::U.matrix(&[PI / 2.0, 0.0, PI]).unwrap();
+        assert!(close(&u, &h));
+
+        assert_eq!(GateType::CX.matrix(&[]).unwrap().len(), 4);
+        let ccx = GateType::CCX.matrix(&[]).unwrap();
+        assert_eq!((ccx.len(), ccx[0].len()), (8, 8));
+
+        assert!(GateType::Custom("foo".into()).matrix(&[]).is_none());
+        assert!(GateType::Barrier.matrix(&[]).is_none());
+        assert!(GateType::RZ.matrix(&[]).is_none());
+        assert!(GateType::H.matrix(&[0.1]).is_none());
+    }
+
     #[test]
     fn test_convention_consistency_cx_simulator_path() {
         use crate::ir::Circuit;
@@ -457,8 +501,14 @@ mod tests {
     #[test]
     fn test_ecr_iswap_arity_and_parse() {
         use std::str::FromStr;
-        assert_eq!(GateType::from_str("ecr").unwrap().num_qubits(), 2);
-        assert_eq!(GateType::from_str("iswap").unwrap().num_qubits(), 2);
+        assert_eq!(
+            GateDefinition::num_qubits(&GateType::from_str("ecr").unwrap()),
+            2
+        );
+        assert_eq!(
+            GateDefinition::num_qubits(&GateType::from_str("iswap").unwrap()),
+            2
+        );
         assert_eq!(GateType::ECR.to_qasm_name(), "ecr");
         assert_eq!(GateType::ISwap.to_qasm_name(), "iswap");
     }
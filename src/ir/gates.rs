@@ -1,7 +1,11 @@
 //! Gate type enumeration.
 
-use std::fmt;
-use std::str::FromStr;
+use super::operations::Operation;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
+use core::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde-ir", derive(serde::Serialize, serde::Deserialize))]
@@ -47,6 +51,45 @@ pub enum GateType {
     Custom(String),
 }
 
+/// Param- and name-free companion of [`GateType`], for grouping gates by
+/// kind (e.g. histogram keys) without stringifying. Every custom gate maps
+/// to [`GateKind::Custom`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde-ir", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum GateKind {
+    H,
+    X,
+    Y,
+    Z,
+    S,
+    Sdg,
+    T,
+    Tdg,
+    ID,
+    RX,
+    RY,
+    RZ,
+    U,
+    CX,
+    CY,
+    CZ,
+    CH,
+    CSX,
+    CRX,
+    CRY,
+    CRZ,
+    RXX,
+    RYY,
+    RZZ,
+    SWAP,
+    CCX,
+    ECR,
+    ISwap,
+    Barrier,
+    Custom,
+}
+
 impl FromStr for GateType {
     type Err = ();
 
@@ -56,7 +99,7 @@ impl FromStr for GateType {
             "x" => GateType::X,
             "y" => GateType::Y,
             "z" => GateType::Z,
-            "cx" => GateType::CX,
+            "cx" | "CX" => GateType::CX,
             "rx" => GateType::RX,
             "ry" => GateType::RY,
             "rz" => GateType::RZ,
@@ -123,6 +166,261 @@ impl GateType {
         }
     }
 
+    /// Number of qubits the gate acts on, or `None` for [`GateType::Custom`]
+    /// (the arity comes from its definition) and [`GateType::Barrier`]
+    /// (which spans any number of qubits).
+    pub fn num_qubits(&self) -> Option<usize> {
+        match self {
+            GateType::H
+            | GateType::X
+            | GateType::Y
+            | GateType::Z
+            | GateType::S
+            | GateType::Sdg
+            | GateType::T
+            | GateType::Tdg
+            | GateType::ID
+            | GateType::RX
+            | GateType::RY
+            | GateType::RZ
+            | GateType::U => Some(1),
+            GateType::CX
+            | GateType::CY
+            | GateType::CZ
+            | GateType::CH
+            | GateType::CSX
+            | GateType::CRX
+            | GateType::CRY
+            | GateType::CRZ
+            | GateType::RXX
+            | GateType::RYY
+            | GateType::RZZ
+            | GateType::SWAP
+            | GateType::ECR
+            | GateType::ISwap => Some(2),
+            GateType::CCX => Some(3),
+            GateType::Barrier | GateType::Custom(_) => None,
+        }
+    }
+
+    /// Returns `true` for gates that take angle parameters: the rotations
+    /// `RX`/`RY`/`RZ`, their controlled and two-qubit forms, and `U`.
+    /// `Custom` gates report `false`; their parameters come from the
+    /// definition.
+    pub fn is_parametric(&self) -> bool {
+        matches!(
+            self,
+            GateType::RX
+                | GateType::RY
+                | GateType::RZ
+                | GateType::U
+                | GateType::CRX
+                | GateType::CRY
+                | GateType::CRZ
+                | GateType::RXX
+                | GateType::RYY
+                | GateType::RZZ
+        )
+    }
+
+    /// Returns the adjoint gate, for daggering and uncomputation.
+    ///
+    /// `S`/`T` swap with `Sdg`/`Tdg`, and self-inverse gates return
+    /// themselves. Parametric gates also return themselves: their inverse
+    /// lives in the angles, see [`GateType::inverse_params`]. Gates with no
+    /// built-in adjoint (`CSX`, `ISwap`, custom gates) map to a custom gate
+    /// named `<name>_dg`, and a `_dg` name maps back, so `inverse` is an
+    /// involution. Those names carry no definition; use
+    /// [`crate::ir::Circuit::inverse`] for an executable adjoint.
+    pub fn inverse(&self) -> GateType {
+        match self {
+            GateType::S => GateType::Sdg,
+            GateType::Sdg => GateType::S,
+            GateType::T => GateType::Tdg,
+            GateType::Tdg => GateType::T,
+            GateType::CSX | GateType::ISwap => {
+                GateType::Custom(format!("{}_dg", self.to_qasm_name()))
+            }
+            GateType::Custom(n) => match n.strip_suffix("_dg") {
+                Some("csx") => GateType::CSX,
+                Some("iswap") => GateType::ISwap,
+                Some(base) => GateType::Custom(base.to_string()),
+                None => GateType::Custom(format!("{n}_dg")),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Returns the parameters of the adjoint of this gate applied with
+    /// `params`: rotation angles are negated and `U(θ, φ, λ)` becomes
+    /// `U(-θ, -λ, -φ)`. Non-parametric gates return `params` unchanged.
+    pub fn inverse_params(&self, params: &[f64]) -> Vec<f64> {
+        match (self, params) {
+            (GateType::U, [theta, phi, lambda]) => vec![-theta, -lambda, -phi],
+            (g, _) if g.is_parametric() => params.iter().map(|p| -p).collect(),
+            _ => params.to_vec(),
+        }
+    }
+
+    /// Returns the singly-controlled form of the gate, taking the control
+    /// as an extra leading qubit: `X`→`CX`, `Y`→`CY`, `Z`→`CZ`, `H`→`CH`
+    /// and `RX`/`RY`/`RZ`→`CRX`/`CRY`/`CRZ` (same parameters). `None` for
+    /// gates without a built-in controlled variant, including `SWAP` (there
+    /// is no `CSWAP` gate type) and `CX`; see
+    /// [`GateType::doubly_controlled`] for `CCX`.
+    pub fn controlled(&self) -> Option<GateType> {
+        Some(match self {
+            GateType::X => GateType::CX,
+            GateType::Y => GateType::CY,
+            GateType::Z => GateType::CZ,
+            GateType::H => GateType::CH,
+            GateType::RX => GateType::CRX,
+            GateType::RY => GateType::CRY,
+            GateType::RZ => GateType::CRZ,
+            _ => return None,
+        })
+    }
+
+    /// Returns the form of the gate with two extra leading controls:
+    /// `X`→`CCX`. `None` for every other gate.
+    pub fn doubly_controlled(&self) -> Option<GateType> {
+        match self {
+            GateType::X => Some(GateType::CCX),
+            _ => None,
+        }
+    }
+
+    /// Returns the gate's [`GateKind`], dropping any custom-gate name.
+    pub fn kind(&self) -> GateKind {
+        match self {
+            GateType::H => GateKind::H,
+            GateType::X => GateKind::X,
+            GateType::Y => GateKind::Y,
+            GateType::Z => GateKind::Z,
+            GateType::S => GateKind::S,
+            GateType::Sdg => GateKind::Sdg,
+            GateType::T => GateKind::T,
+            GateType::Tdg => GateKind::Tdg,
+            GateType::ID => GateKind::ID,
+            GateType::RX => GateKind::RX,
+            GateType::RY => GateKind::RY,
+            GateType::RZ => GateKind::RZ,
+            GateType::U => GateKind::U,
+            GateType::CX => GateKind::CX,
+            GateType::CY => GateKind::CY,
+            GateType::CZ => GateKind::CZ,
+            GateType::CH => GateKind::CH,
+            GateType::CSX => GateKind::CSX,
+            GateType::CRX => GateKind::CRX,
+            GateType::CRY => GateKind::CRY,
+            GateType::CRZ => GateKind::CRZ,
+            GateType::RXX => GateKind::RXX,
+            GateType::RYY => GateKind::RYY,
+            GateType::RZZ => GateKind::RZZ,
+            GateType::SWAP => GateKind::SWAP,
+            GateType::CCX => GateKind::CCX,
+            GateType::ECR => GateKind::ECR,
+            GateType::ISwap => GateKind::ISwap,
+            GateType::Barrier => GateKind::Barrier,
+            GateType::Custom(_) => GateKind::Custom,
+        }
+    }
+
+    /// Canonical decomposition of a parameter-free multi-qubit gate into
+    /// simpler standard gates, following the `qelib1.inc` definitions:
+    /// SWAP → 3 CX, CZ → H·CX·H, CY → Sdg·CX·S, CH, iSWAP, and the 6-CX
+    /// Toffoli. `qubits` are the operands in gate order.
+    ///
+    /// Returns `None` for gates that are already primitive, for parametric
+    /// gates (use [`crate::ir::gate_def::GateDefinition::decompose`], which
+    /// takes parameters), for gates with no canonical form here (ECR, CSX)
+    /// and for `Custom`, or if `qubits` has the wrong length.
+    pub fn decompose_default(&self, qubits: &[usize]) -> Option<Vec<Operation>> {
+        let g = |name: GateType, qubits: &[usize]| Operation::Gate {
+            name,
+            qubits: qubits.to_vec(),
+            params: Vec::new(),
+        };
+        let ops = match (self, qubits) {
+            (GateType::SWAP, &[a, b]) => vec![
+                g(GateType::CX, &[a, b]),
+                g(GateType::CX, &[b, a]),
+                g(GateType::CX, &[a, b]),
+            ],
+            (GateType::CZ, &[c, t]) => vec![
+                g(GateType::H, &[t]),
+                g(GateType::CX, &[c, t]),
+                g(GateType::H, &[t]),
+            ],
+            (GateType::CY, &[c, t]) => vec![
+                g(GateType::Sdg, &[t]),
+                g(GateType::CX, &[c, t]),
+                g(GateType::S, &[t]),
+            ],
+            (GateType::CH, &[c, t]) => vec![
+                g(GateType::H, &[t]),
+                g(GateType::Sdg, &[t]),
+                g(GateType::CX, &[c, t]),
+                g(GateType::H, &[t]),
+                g(GateType::T, &[t]),
+                g(GateType::CX, &[c, t]),
+                g(GateType::T, &[t]),
+                g(GateType::H, &[t]),
+                g(GateType::S, &[t]),
+                g(GateType::X, &[t]),
+                g(GateType::S, &[c]),
+            ],
+            (GateType::ISwap, &[a, b]) => vec![
+                g(GateType::S, &[a]),
+                g(GateType::S, &[b]),
+                g(GateType::H, &[a]),
+                g(GateType::CX, &[a, b]),
+                g(GateType::CX, &[b, a]),
+                g(GateType::H, &[b]),
+            ],
+            (GateType::CCX, &[a, b, t]) => vec![
+                g(GateType::H, &[t]),
+                g(GateType::CX, &[b, t]),
+                g(GateType::Tdg, &[t]),
+                g(GateType::CX, &[a, t]),
+                g(GateType::T, &[t]),
+                g(GateType::CX, &[b, t]),
+                g(GateType::Tdg, &[t]),
+                g(GateType::CX, &[a, t]),
+                g(GateType::T, &[b]),
+                g(GateType::T, &[t]),
+                g(GateType::H, &[t]),
+                g(GateType::CX, &[a, b]),
+                g(GateType::T, &[a]),
+                g(GateType::Tdg, &[b]),
+                g(GateType::CX, &[a, b]),
+            ],
+            _ => return None,
+        };
+        Some(ops)
+    }
+
+    /// Returns true if the gate is diagonal in the computational basis.
+    ///
+    /// Diagonal gates commute with each other and with Z-basis measurement,
+    /// which optimization passes rely on when reordering or dropping gates
+    /// ahead of a measure. `Custom` gates are conservatively non-diagonal.
+    pub fn is_diagonal(&self) -> bool {
+        matches!(
+            self,
+            GateType::ID
+                | GateType::Z
+                | GateType::S
+                | GateType::Sdg
+                | GateType::T
+                | GateType::Tdg
+                | GateType::RZ
+                | GateType::CZ
+                | GateType::CRZ
+                | GateType::RZZ
+        )
+    }
+
     pub fn static_qasm_name(&self) -> Option<&'static str> {
         Some(match self {
             GateType::H => "h",
@@ -164,3 +462,282 @@ impl fmt::Display for GateType {
         f.write_str(self.to_qasm_name())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_diagonal() {
+        assert!(GateType::RZ.is_diagonal());
+        assert!(GateType::Z.is_diagonal());
+        assert!(GateType::CZ.is_diagonal());
+        assert!(!GateType::H.is_diagonal());
+        assert!(!GateType::X.is_diagonal());
+        assert!(!GateType::Custom("rz_like".into()).is_diagonal());
+    }
+
+    fn gate(name: GateType, qubits: &[usize]) -> Operation {
+        Operation::Gate {
+            name,
+            qubits: qubits.to_vec(),
+            params: vec![],
+        }
+    }
+
+    #[test]
+    fn test_num_qubits_and_is_parametric() {
+        use GateType::*;
+        let cases = [
+            (H, Some(1), false),
+            (X, Some(1), false),
+            (Y, Some(1), false),
+            (Z, Some(1), false),
+            (S, Some(1), false),
+            (Sdg, Some(1), false),
+            (T, Some(1), false),
+            (Tdg, Some(1), false),
+            (ID, Some(1), false),
+            (RX, Some(1), true),
+            (RY, Some(1), true),
+            (RZ, Some(1), true),
+            (U, Some(1), true),
+            (CX, Some(2), false),
+            (CY, Some(2), false),
+            (CZ, Some(2), false),
+            (CH, Some(2), false),
+            (CSX, Some(2), false),
+            (CRX, Some(2), true),
+            (CRY, Some(2), true),
+            (CRZ, Some(2), true),
+            (RXX, Some(2), true),
+            (RYY, Some(2), true),
+            (RZZ, Some(2), true),
+            (SWAP, Some(2), false),
+            (ECR, Some(2), false),
+            (ISwap, Some(2), false),
+            (CCX, Some(3), false),
+            (Barrier, None, false),
+            (Custom("foo".into()), None, false),
+        ];
+        for (gate, arity, parametric) in cases {
+            assert_eq!(gate.num_qubits(), arity, "{gate:?}");
+            assert_eq!(gate.is_parametric(), parametric, "{gate:?}");
+        }
+    }
+
+    #[test]
+    fn test_inverse_is_an_involution() {
+        use GateType::*;
+        let gates = [
+            H,
+            X,
+            Y,
+            Z,
+            S,
+            Sdg,
+            T,
+            Tdg,
+            ID,
+            RX,
+            RY,
+            RZ,
+            U,
+            CX,
+            CY,
+            CZ,
+            CH,
+            CSX,
+            CRX,
+            CRY,
+            CRZ,
+            RXX,
+            RYY,
+            RZZ,
+            SWAP,
+            CCX,
+            ECR,
+            ISwap,
+            Barrier,
+            Custom("foo".into()),
+        ];
+        for g in gates {
+            assert_eq!(g.inverse().inverse(), g, "{g:?}");
+        }
+        assert_eq!(S.inverse(), Sdg);
+        assert_eq!(T.inverse(), Tdg);
+        assert_eq!(H.inverse(), H);
+        assert_eq!(Custom("foo".into()).inverse(), Custom("foo_dg".into()));
+        assert_eq!(CSX.inverse(), Custom("csx_dg".into()));
+        assert_eq!(U.inverse_params(&[0.1, 0.2, 0.3]), vec![-0.1, -0.3, -0.2]);
+        assert_eq!(RZ.inverse_params(&[0.5]), vec![-0.5]);
+        assert_eq!(H.inverse_params(&[]), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn test_inverse_undoes_gate() {
+        use crate::ir::Circuit;
+        use GateType::*;
+        for name in [
+            H, X, Y, Z, S, Sdg, T, Tdg, ID, RX, RY, RZ, U, CX, CY, CZ, CH, CRX, CRY, CRZ, RXX, RYY,
+            RZZ, SWAP, CCX, ECR,
+        ] {
+            let n = name.num_qubits().unwrap();
+            let qubits: Vec<usize> = (0..n).collect();
+            let params: Vec<f64> = match name {
+                U => vec![0.3, 0.7, -1.1],
+                ref g if g.is_parametric() => vec![0.9],
+                _ => vec![],
+            };
+            let mut circuit = Circuit::new(n, 0);
+            circuit.add_op(Operation::Gate {
+                name: name.clone(),
+                qubits: qubits.clone(),
+                params: params.clone(),
+            });
+            circuit.add_op(Operation::Gate {
+                name: name.inverse(),
+                qubits,
+                params: name.inverse_params(&params),
+            });
+            circuit
+                .verify_against(&Circuit::new(n, 0), 1e-9)
+                .unwrap_or_else(|e| panic!("{name}: {e}"));
+        }
+    }
+
+    #[test]
+    fn test_controlled_variants() {
+        use crate::ir::Circuit;
+        use GateType::*;
+        for (base, controlled, params) in [
+            (X, CX, vec![]),
+            (Y, CY, vec![]),
+            (Z, CZ, vec![]),
+            (H, CH, vec![]),
+            (RX, CRX, vec![0.7]),
+            (RY, CRY, vec![0.7]),
+            (RZ, CRZ, vec![0.7]),
+        ] {
+            assert_eq!(base.controlled(), Some(controlled.clone()), "{base:?}");
+            // C(G) = |0><0| ⊗ I + |1><1| ⊗ G, so applying it once with the
+            // control flipped and once without applies G exactly once.
+            let n = controlled.num_qubits().unwrap();
+            let qubits: Vec<usize> = (0..n).collect();
+            let mut with_control = Circuit::new(n, 0);
+            for _ in 0..2 {
+                with_control.add_op(Operation::Gate {
+                    name: controlled.clone(),
+                    qubits: qubits.clone(),
+                    params: params.clone(),
+                });
+                with_control.add_op(gate(X, &[0]));
+            }
+            let mut direct = Circuit::new(n, 0);
+            direct.add_op(Operation::Gate {
+                name: base.clone(),
+                qubits: qubits[1..].to_vec(),
+                params,
+            });
+            with_control
+                .verify_against(&direct, 1e-9)
+                .unwrap_or_else(|e| panic!("{base}: {e}"));
+        }
+        for g in [SWAP, S, T, U, ID, CX, CCX, Custom("foo".into())] {
+            assert_eq!(g.controlled(), None, "{g:?}");
+        }
+        assert_eq!(X.doubly_controlled(), Some(CCX));
+        for g in [CX, Y, Z, H, RX] {
+            assert_eq!(g.doubly_controlled(), None, "{g:?}");
+        }
+    }
+
+    #[test]
+    fn test_decompose_default_swap_and_cz() {
+        assert_eq!(
+            GateType::SWAP.decompose_default(&[0, 1]).unwrap(),
+            vec![
+                gate(GateType::CX, &[0, 1]),
+                gate(GateType::CX, &[1, 0]),
+                gate(GateType::CX, &[0, 1]),
+            ]
+        );
+        assert_eq!(
+            GateType::CZ.decompose_default(&[2, 0]).unwrap(),
+            vec![
+                gate(GateType::H, &[0]),
+                gate(GateType::CX, &[2, 0]),
+                gate(GateType::H, &[0]),
+            ]
+        );
+        assert!(GateType::CX.decompose_default(&[0, 1]).is_none());
+        assert!(GateType::H.decompose_default(&[0]).is_none());
+        assert!(GateType::CRZ.decompose_default(&[0, 1]).is_none());
+        assert!(GateType::Custom("foo".into())
+            .decompose_default(&[0, 1])
+            .is_none());
+        assert!(GateType::SWAP.decompose_default(&[0]).is_none());
+    }
+
+    #[test]
+    fn test_decompose_default_preserves_unitary() {
+        use crate::ir::Circuit;
+        for (name, n) in [
+            (GateType::SWAP, 2),
+            (GateType::CZ, 2),
+            (GateType::CY, 2),
+            (GateType::CH, 2),
+            (GateType::ISwap, 2),
+            (GateType::CCX, 3),
+        ] {
+            let qubits: Vec<usize> = (0..n).collect();
+            let mut original = Circuit::new(n, 0);
+            original.add_op(gate(name.clone(), &qubits));
+            let mut decomposed = Circuit::new(n, 0);
+            for op in name.decompose_default(&qubits).unwrap() {
+                decomposed.add_op(op);
+            }
+            original
+                .verify_against(&decomposed, 1e-9)
+                .unwrap_or_else(|e| panic!("{name}: {e}"));
+        }
+    }
+
+    #[test]
+    fn test_kind_groups_across_params() {
+        use crate::ir::Operation;
+        use std::collections::HashMap;
+
+        let ops = [
+            Operation::Gate {
+                name: GateType::RX,
+                qubits: vec![0],
+                params: vec![0.1],
+            },
+            Operation::Gate {
+                name: GateType::RX,
+                qubits: vec![1],
+                params: vec![0.2],
+            },
+            Operation::Gate {
+                name: GateType::Custom("foo".into()),
+                qubits: vec![0],
+                params: vec![],
+            },
+            Operation::Gate {
+                name: GateType::Custom("bar".into()),
+                qubits: vec![0],
+                params: vec![],
+            },
+        ];
+        let mut histogram: HashMap<GateKind, usize> = HashMap::new();
+        for op in &ops {
+            if let Operation::Gate { name, .. } = op {
+                *histogram.entry(name.kind()).or_insert(0) += 1;
+            }
+        }
+        assert_eq!(histogram[&GateKind::RX], 2);
+        assert_eq!(histogram[&GateKind::Custom], 2);
+        assert_eq!(histogram.len(), 2);
+    }
+}
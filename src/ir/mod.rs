@@ -1,15 +1,27 @@
 //! Intermediate Representation (IR).
+//!
+//! Builds on `core` + `alloc` when the `std` feature is off; the methods that
+//! call into the transpiler, simulator or verifier are `std`-only.
 
 pub mod ast;
 pub mod circuit;
+#[cfg(feature = "std")]
+pub mod dag;
 pub mod gate_def;
 pub mod gates;
 pub mod operations;
 pub mod registry;
 pub mod signature;
 
-pub use circuit::Circuit;
+pub use circuit::{Circuit, Register};
 pub use gate_def::GateDefinition;
-pub use gates::GateType;
+pub use gates::{GateKind, GateType};
 pub use operations::{ClassicalCondition, Operation};
 pub use signature::{CommutationSignature, PauliBasis, SymbolicAngle, SymbolicFraction};
+
+// `std`'s maps seed their hasher from the OS, so `no_std` builds fall back
+// to `hashbrown` (the same table `std` wraps).
+#[cfg(not(feature = "std"))]
+pub(crate) use hashbrown::{HashMap, HashSet};
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{HashMap, HashSet};
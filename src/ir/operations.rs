@@ -1,7 +1,14 @@
 //! Circuit operations (gates, measurements, resets, barriers, conditionals).
 
+use super::circuit::Register;
 use super::gates::GateType;
-use std::fmt;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use num_traits::Float;
 
 /// A classical condition used to guard a conditional operation.
 ///
@@ -42,22 +49,98 @@ pub enum Operation {
     },
 }
 
+/// Export options for [`Operation`]'s QASM writer.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct QasmStyle<'a> {
+    /// Spell the built-ins as `U`/`CX` (no `qelib1.inc` needed).
+    pub primitive: bool,
+    /// Named quantum registers; empty means a single flat `q` register.
+    pub qregs: &'a [Register],
+    /// Named classical registers; empty means a single flat `c` register.
+    pub cregs: &'a [Register],
+    /// Emit OpenQASM 3 statement syntax instead of 2.0.
+    pub qasm3: bool,
+}
+
+impl QasmStyle<'_> {
+    fn write_bit<W: fmt::Write>(
+        w: &mut W,
+        regs: &[Register],
+        flat: &str,
+        index: usize,
+    ) -> fmt::Result {
+        match regs.iter().find(|r| r.contains(index)) {
+            Some(r) => write!(w, "{}[{}]", r.name, index - r.start),
+            None => write!(w, "{}[{}]", flat, index),
+        }
+    }
+}
+
+/// Writes a gate parameter so that it parses back to the same `f64`: as
+/// `pi`, `-pi/2`, `3*pi/4`, ... when it is exactly (to the last bit, as the
+/// parser evaluates that expression) `k/d·π` with `d ≤ 16` and `|k/d| ≤ 4`,
+/// and as the shortest round-tripping decimal otherwise.
+fn write_angle<W: fmt::Write>(w: &mut W, angle: f64) -> fmt::Result {
+    use core::f64::consts::PI;
+    if angle != 0.0 && angle.is_finite() {
+        let mag = angle.abs();
+        let sign = if angle < 0.0 { "-" } else { "" };
+        for d in 1..=16u32 {
+            let k = (mag * f64::from(d) / PI).round();
+            if k < 1.0 || k > 4.0 * f64::from(d) {
+                continue;
+            }
+            let numerator = if k == 1.0 { PI } else { k * PI };
+            let value = if d == 1 {
+                numerator
+            } else {
+                numerator / f64::from(d)
+            };
+            if value == mag {
+                return match (k as u32, d) {
+                    (1, 1) => write!(w, "{sign}pi"),
+                    (k, 1) => write!(w, "{sign}{k}*pi"),
+                    (1, d) => write!(w, "{sign}pi/{d}"),
+                    (k, d) => write!(w, "{sign}{k}*pi/{d}"),
+                };
+            }
+        }
+    }
+    write!(w, "{}", angle)
+}
+
 impl Operation {
     fn write_qasm<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        self.write_qasm_with(w, &QasmStyle::default())
+    }
+
+    /// Writes the QASM form of this operation. In `primitive` mode the two
+    /// built-in OpenQASM 2.0 gates are spelled `U` and `CX` so the output
+    /// does not depend on `qelib1.inc`.
+    pub(crate) fn write_qasm_with<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        style: &QasmStyle<'_>,
+    ) -> fmt::Result {
         match self {
             Operation::Gate {
                 name,
                 qubits,
                 params,
             } => {
-                w.write_str(name.to_qasm_name())?;
+                let gate_name = match name {
+                    GateType::U if style.primitive || style.qasm3 => "U",
+                    GateType::CX if style.primitive => "CX",
+                    other => other.to_qasm_name(),
+                };
+                w.write_str(gate_name)?;
                 if !params.is_empty() {
                     w.write_char('(')?;
                     for (i, p) in params.iter().enumerate() {
                         if i > 0 {
                             w.write_str(", ")?;
                         }
-                        write!(w, "{:.10}", p)?;
+                        write_angle(w, *p)?;
                     }
                     w.write_char(')')?;
                 }
@@ -66,27 +149,66 @@ impl Operation {
                     if i > 0 {
                         w.write_str(", ")?;
                     }
-                    write!(w, "q[{}]", q)?;
+                    QasmStyle::write_bit(w, style.qregs, "q", *q)?;
                 }
                 w.write_char(';')
             }
+            Operation::Measure { qubit, cbit } if style.qasm3 => {
+                QasmStyle::write_bit(w, style.cregs, "c", *cbit)?;
+                w.write_str(" = measure ")?;
+                QasmStyle::write_bit(w, style.qregs, "q", *qubit)?;
+                w.write_char(';')
+            }
             Operation::Measure { qubit, cbit } => {
-                write!(w, "measure q[{}] -> c[{}];", qubit, cbit)
+                w.write_str("measure ")?;
+                QasmStyle::write_bit(w, style.qregs, "q", *qubit)?;
+                w.write_str(" -> ")?;
+                QasmStyle::write_bit(w, style.cregs, "c", *cbit)?;
+                w.write_char(';')
+            }
+            Operation::Reset { qubit } => {
+                w.write_str("reset ")?;
+                QasmStyle::write_bit(w, style.qregs, "q", *qubit)?;
+                w.write_char(';')
             }
-            Operation::Reset { qubit } => write!(w, "reset q[{}];", qubit),
             Operation::Barrier { qubits } => {
                 w.write_str("barrier ")?;
-                for (i, q) in qubits.iter().enumerate() {
+                // A run covering a whole named register, in order, is
+                // written as the bare register name.
+                let mut i = 0;
+                while i < qubits.len() {
                     if i > 0 {
                         w.write_str(", ")?;
                     }
-                    write!(w, "q[{}]", q)?;
+                    let whole = style.qregs.iter().find(|r| {
+                        r.size > 1
+                            && qubits.len() - i >= r.size
+                            && qubits[i..i + r.size]
+                                .iter()
+                                .copied()
+                                .eq(r.start..r.start + r.size)
+                    });
+                    match whole {
+                        Some(r) => {
+                            w.write_str(&r.name)?;
+                            i += r.size;
+                        }
+                        None => {
+                            QasmStyle::write_bit(w, style.qregs, "q", qubits[i])?;
+                            i += 1;
+                        }
+                    }
                 }
                 w.write_char(';')
             }
+            Operation::Conditional { condition, op } if style.qasm3 => {
+                write!(w, "if ({} == {}) {{ ", condition.creg, condition.value)?;
+                op.write_qasm_with(w, style)?;
+                w.write_str(" }")
+            }
             Operation::Conditional { condition, op } => {
                 write!(w, "if({}=={}) ", condition.creg, condition.value)?;
-                op.write_qasm(w)
+                op.write_qasm_with(w, style)
             }
         }
     }
@@ -102,12 +224,39 @@ impl Operation {
         match self {
             Operation::Gate { qubits, .. } | Operation::Barrier { qubits } => qubits,
             Operation::Measure { qubit, .. } | Operation::Reset { qubit } => {
-                std::slice::from_ref(qubit)
+                core::slice::from_ref(qubit)
             }
             Operation::Conditional { op, .. } => op.qubits(),
         }
     }
 
+    /// Rewrites every qubit index `q` (including inside a conditional) to
+    /// `f(q)`.
+    pub(crate) fn map_qubits(&mut self, f: &mut impl FnMut(usize) -> usize) {
+        match self {
+            Operation::Gate { qubits, .. } | Operation::Barrier { qubits } => {
+                for q in qubits {
+                    *q = f(*q);
+                }
+            }
+            Operation::Measure { qubit, .. } | Operation::Reset { qubit } => *qubit = f(*qubit),
+            Operation::Conditional { op, .. } => op.map_qubits(f),
+        }
+    }
+
+    /// Name under which this operation's duration is looked up: the gate
+    /// name, `measure` or `reset` (conditionals use their inner
+    /// operation). `None` for barriers, which take no time.
+    pub(crate) fn duration_key(&self) -> Option<&str> {
+        match self {
+            Operation::Gate { name, .. } => Some(name.to_qasm_name()),
+            Operation::Measure { .. } => Some("measure"),
+            Operation::Reset { .. } => Some("reset"),
+            Operation::Barrier { .. } => None,
+            Operation::Conditional { op, .. } => op.duration_key(),
+        }
+    }
+
     /// Returns true if this operation is a barrier. Optimization passes
     /// must not reorder/combine operations across barriers.
     #[inline]
@@ -120,6 +269,40 @@ impl Operation {
     pub fn is_conditional(&self) -> bool {
         matches!(self, Operation::Conditional { .. })
     }
+
+    /// Like `==`, but gate parameters only need to agree to within `eps`.
+    pub fn approx_eq(&self, other: &Operation, eps: f64) -> bool {
+        match (self, other) {
+            (
+                Operation::Gate {
+                    name: n1,
+                    qubits: q1,
+                    params: p1,
+                },
+                Operation::Gate {
+                    name: n2,
+                    qubits: q2,
+                    params: p2,
+                },
+            ) => {
+                n1 == n2
+                    && q1 == q2
+                    && p1.len() == p2.len()
+                    && p1.iter().zip(p2).all(|(a, b)| (a - b).abs() <= eps)
+            }
+            (
+                Operation::Conditional {
+                    condition: c1,
+                    op: o1,
+                },
+                Operation::Conditional {
+                    condition: c2,
+                    op: o2,
+                },
+            ) => c1 == c2 && o1.approx_eq(o2, eps),
+            _ => self == other,
+        }
+    }
 }
 
 impl fmt::Display for Operation {
@@ -1,7 +1,16 @@
 //! Custom gate definition registry.
 
+use crate::error::{QRustError, Result};
 use crate::ir::ast::ParsedStatement;
-use std::collections::HashMap;
+use crate::ir::HashMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use nalgebra::DMatrix;
+use num_complex::Complex;
+
+/// Tolerance for the `U†U = I` check in [`GateRegistry::register_unitary`].
+const UNITARY_TOLERANCE: f64 = 1e-8;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct CustomGateDef {
@@ -13,6 +22,9 @@ pub struct CustomGateDef {
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct GateRegistry {
     pub defs: HashMap<String, CustomGateDef>,
+    /// Custom gates defined directly by a matrix rather than a gate body.
+    /// The simulator applies these as-is; they are not unrolled.
+    pub unitaries: HashMap<String, DMatrix<Complex<f64>>>,
 }
 
 impl GateRegistry {
@@ -41,4 +53,37 @@ impl GateRegistry {
     pub fn get(&self, name: &str) -> Option<&CustomGateDef> {
         self.defs.get(name)
     }
+
+    /// Registers a custom gate by its unitary matrix (qubit 0 is the least
+    /// significant bit of the row/column index, as in the simulator).
+    ///
+    /// # Errors
+    /// Returns [`QRustError::SizeMismatch`] if `matrix` is not square with
+    /// dimension `2^k` (`k >= 1`), and [`QRustError::InvalidConfig`] if it is
+    /// not unitary within tolerance.
+    pub fn register_unitary(&mut self, name: String, matrix: DMatrix<Complex<f64>>) -> Result<()> {
+        let dim = matrix.nrows();
+        if dim != matrix.ncols() || dim < 2 || !dim.is_power_of_two() {
+            return Err(QRustError::SizeMismatch(format!(
+                "unitary for custom gate '{}' must be square with dimension 2^k, got {}x{}",
+                name,
+                matrix.nrows(),
+                matrix.ncols()
+            )));
+        }
+        let deviation = (matrix.adjoint() * &matrix - DMatrix::identity(dim, dim)).norm();
+        if deviation > UNITARY_TOLERANCE {
+            return Err(QRustError::InvalidConfig(format!(
+                "matrix for custom gate '{}' is not unitary (|U'U - I| = {:.3e})",
+                name, deviation
+            )));
+        }
+        self.unitaries.insert(name, matrix);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn unitary(&self, name: &str) -> Option<&DMatrix<Complex<f64>>> {
+        self.unitaries.get(name)
+    }
 }
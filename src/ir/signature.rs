@@ -1,6 +1,7 @@
 //! Algebraic signatures used by commutation and simplification analyses.
 
-use std::f64::consts::PI;
+use alloc::vec::Vec;
+use core::f64::consts::PI;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
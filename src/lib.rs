@@ -49,7 +49,15 @@
 //!
 //! - `serde-ir` — derives `Serialize`/`Deserialize` on the IR types
 //!   ([`ir::Circuit`], [`ir::Operation`], [`ir::GateType`],
-//!   [`ir::ClassicalCondition`]). Off by default to keep the dep graph minimal.
+//!   [`ir::ClassicalCondition`]) and enables the `Circuit::to_json` /
+//!   `Circuit::from_json` helpers. Off by default to keep the dep graph minimal.
+//! - `std` (default) — everything outside [`ir`] and [`error`]: the parser,
+//!   simulator, backends, transpiler, verification, and the `qrust` binary.
+//!   With `default-features = false` the crate is `no_std + alloc` and
+//!   exposes only the IR for building and inspecting circuits; this needs
+//!   rustc 1.81+ for `core::error::Error`.
+//! - `test-utils` — exposes `q_rust::test_utils`, assertion helpers for testing
+//!   custom transpiler passes.
 //!
 //! ## Diagnostics
 //!
@@ -58,6 +66,7 @@
 //! warning-level diagnostics (e.g. KAK fallback notifications, custom-gate
 //! unroll failures).
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(rust_2018_idioms)]
 #![warn(missing_debug_implementations)]
 #![allow(
@@ -68,15 +77,25 @@
     clippy::redundant_closure
 )]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod backend;
 pub mod error;
 pub mod ir;
+#[cfg(feature = "std")]
 pub mod parser;
+#[cfg(feature = "std")]
 pub mod simulator;
+#[cfg(all(feature = "std", any(test, feature = "test-utils")))]
+pub mod test_utils;
+#[cfg(feature = "std")]
 pub mod transpiler;
+#[cfg(feature = "std")]
 pub mod verify;
 
 pub use error::{QRustError, Result};
+#[cfg(feature = "std")]
 pub use verify::{verify_equivalence, Verdict};
 
 /// Bit-shift guard for `n`-qubit systems: the simulator and embedding routines
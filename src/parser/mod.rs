@@ -1,14 +1,72 @@
 //! OpenQASM 2.0 parser.
 
+pub mod qelib1;
 pub mod rules;
 
-use self::rules::{comment, creg, gate_call, include, measure, openqasm_version, qreg};
+use self::rules::{
+    block_comment, comment, creg, gate_call, include, measure, openqasm_version, qreg,
+};
 use crate::error::{QRustError, Result};
 use crate::ir::ast::{Expr, ParsedStatement};
-use crate::ir::{Circuit, ClassicalCondition, GateType, Operation};
+use crate::ir::{Circuit, ClassicalCondition, GateType, Operation, Register};
 use nom::{branch::alt, character::complete::multispace0};
 use std::collections::HashMap;
 use std::f64::consts::PI;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// A syntax error located in the QASM source, surfaced as
+/// [`QRustError::Syntax`].
+///
+/// `line` and `column` are 1-based and count characters, not bytes, so they
+/// match what an editor shows for the offending position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line of the offending text.
+    pub line: usize,
+    /// 1-based column (in characters) of the offending text.
+    pub column: usize,
+    /// What went wrong, e.g. `"parse error"`.
+    pub message: String,
+    /// The offending text up to the end of its line, truncated to a few
+    /// dozen characters so the message stays on one line.
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// Builds an error for the position where `rest` starts within `source`.
+    /// `rest` must be a suffix of `source`.
+    fn at(source: &str, rest: &str, message: impl Into<String>, snippet_len: usize) -> Self {
+        let consumed = &source[..source.len() - rest.len()];
+        let line = consumed.matches('\n').count() + 1;
+        let line_start = consumed.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            line,
+            column: consumed[line_start..].chars().count() + 1,
+            message: message.into(),
+            snippet: rest
+                .lines()
+                .next()
+                .unwrap_or("")
+                .chars()
+                .take(snippet_len)
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if !self.snippet.is_empty() {
+            write!(f, " near: {}", self.snippet)?;
+        }
+        write!(f, " (line {}, column {})", self.line, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
 
 fn resolve_argument(
     arg: &(String, Option<usize>),
@@ -46,16 +104,167 @@ fn resolve_argument(
     }
 }
 
+/// A parsed `gate` definition: parameter names, qubit names and body.
+type GateDefParts = (Vec<String>, Vec<String>, Vec<ParsedStatement>);
+
 #[derive(Default)]
 struct ParseContext {
     qregs: HashMap<String, (usize, usize)>,
     cregs: HashMap<String, (usize, usize)>,
-    gate_defs: HashMap<String, (Vec<String>, Vec<String>, Vec<ParsedStatement>)>,
+    gate_defs: HashMap<String, GateDefParts>,
+    /// Set by `include "qelib1.inc";`: library gates become callable.
+    qelib1: bool,
+    /// `// @region-begin` markers not yet closed: name -> first op index.
+    open_regions: HashMap<String, usize>,
+    /// Constants defined with `// @param name = expr`.
+    params: HashMap<String, f64>,
+    /// Accept calls to undefined gates as opaque custom gates.
+    lenient: bool,
+    /// Directories searched, in order, by `include` statements other than
+    /// `qelib1.inc`. Empty means such includes are rejected.
+    search_paths: Vec<PathBuf>,
+    /// Canonical paths of the files currently being included, outermost
+    /// first, to detect include cycles.
+    include_stack: Vec<PathBuf>,
+    /// Gate-name aliases from [`parse_qasm_with_aliases`].
+    aliases: HashMap<String, String>,
+}
+
+/// Definitions from [`qelib1::QELIB1_INC`] for the gates without a built-in
+/// [`GateType`], parsed on first use.
+fn qelib1_defs() -> &'static HashMap<String, GateDefParts> {
+    static DEFS: OnceLock<HashMap<String, GateDefParts>> = OnceLock::new();
+    DEFS.get_or_init(|| {
+        let mut defs = HashMap::new();
+        let mut rest = qelib1::QELIB1_INC;
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            if let Ok((rem, _)) = comment(rest) {
+                rest = rem;
+                continue;
+            }
+            let (rem, stmt) =
+                rules::gate_def(rest).expect("embedded qelib1.inc only contains gate definitions");
+            rest = rem;
+            if let ParsedStatement::GateDef(name, params, qubits, body) = stmt {
+                if matches!(name.parse::<GateType>(), Ok(GateType::Custom(_))) {
+                    defs.insert(name, (params, qubits, body));
+                }
+            }
+        }
+        defs
+    })
+}
+
+/// Registers the `qelib1.inc` gate `name`, and the library gates its body
+/// calls, on `circuit` so it can be decomposed and simulated. Does nothing
+/// for other names or gates the circuit already defines.
+fn register_qelib1_gate(circuit: &mut Circuit, name: &str) {
+    let Some((params, qubits, body)) = qelib1_defs().get(name) else {
+        return;
+    };
+    if circuit.custom_gates.get(name).is_some() {
+        return;
+    }
+    circuit.register_custom_gate(
+        name.to_string(),
+        params.clone(),
+        qubits.clone(),
+        body.clone(),
+    );
+    for stmt in body {
+        if let ParsedStatement::Gate(callee, _, _) = stmt {
+            register_qelib1_gate(circuit, callee);
+        }
+    }
 }
 
 pub fn parse_qasm(input: &str) -> Result<Circuit> {
+    parse_with_context(input, ParseContext::default())
+}
+
+/// Like [`parse_qasm`], but calls to gates that are neither standard nor
+/// defined in the source become opaque [`GateType::Custom`] operations
+/// instead of [`QRustError::UnknownGate`] errors.
+///
+/// Meant for inspecting circuits that use vendor gates the crate does not
+/// model, e.g. with [`Circuit::draw`]. Such circuits cannot be simulated or
+/// decomposed.
+pub fn parse_qasm_lenient(input: &str) -> Result<Circuit> {
+    parse_with_context(
+        input,
+        ParseContext {
+            lenient: true,
+            ..ParseContext::default()
+        },
+    )
+}
+
+/// Like [`parse_qasm`], but `include "file";` statements other than
+/// `qelib1.inc` are resolved against `search_paths`: the first directory
+/// containing `file` wins, and the file's statements (typically gate
+/// definitions and register declarations) are spliced in at the point of
+/// the include. Included files may include further files; they do not
+/// start with an `OPENQASM` header.
+///
+/// # Errors
+/// Returns [`QRustError::ParseError`] listing the attempted paths when an
+/// included file is not found, or naming the chain of files when includes
+/// form a cycle.
+pub fn parse_qasm_with_includes(input: &str, search_paths: &[PathBuf]) -> Result<Circuit> {
+    parse_with_context(
+        input,
+        ParseContext {
+            search_paths: search_paths.to_vec(),
+            ..ParseContext::default()
+        },
+    )
+}
+
+/// Like [`parse_qasm`], but gate calls are first renamed through
+/// `aliases`, so vendor-specific names can be read without forking the
+/// parser. A target that is a standard gate or a gate defined in the source
+/// resolves to it; any other target becomes an opaque
+/// [`GateType::Custom`] gate of that name. Mapping a name to itself keeps
+/// it as an opaque custom gate even if a built-in has the same name.
+///
+/// Aliases apply to gate calls, not to `gate` definitions or their bodies.
+pub fn parse_qasm_with_aliases(input: &str, aliases: &HashMap<String, String>) -> Result<Circuit> {
+    parse_with_context(
+        input,
+        ParseContext {
+            aliases: aliases.clone(),
+            ..ParseContext::default()
+        },
+    )
+}
+
+/// Consumes one `//` or `/* */` comment at the start of `input`, returning
+/// the rest, or `None` if `input` does not start with a comment. `source` is
+/// the whole text being parsed and is only used to locate errors.
+fn skip_comment<'a>(source: &str, input: &'a str) -> Result<Option<&'a str>> {
+    if let Ok((rem, _)) = comment(input) {
+        return Ok(Some(rem));
+    }
+    if input.starts_with("/*") {
+        return match block_comment(input) {
+            Ok((rem, _)) => Ok(Some(rem)),
+            Err(_) => Err(QRustError::Syntax(ParseError::at(
+                source,
+                input,
+                "unterminated block comment (missing `*/`)",
+                30,
+            ))),
+        };
+    }
+    Ok(None)
+}
+
+fn parse_with_context(input: &str, mut ctx: ParseContext) -> Result<Circuit> {
     let mut circuit = Circuit::new(0, 0);
-    let mut ctx = ParseContext::default();
     let mut total_qubits = 0;
     let mut total_cbits = 0;
 
@@ -70,7 +279,7 @@ pub fn parse_qasm(input: &str) -> Result<Circuit> {
                 "Empty source or missing OPENQASM header".into(),
             ));
         }
-        if let Ok((rem, _)) = comment(current) {
+        if let Some(rem) = skip_comment(input, current)? {
             current = rem;
             continue;
         }
@@ -78,9 +287,17 @@ pub fn parse_qasm(input: &str) -> Result<Circuit> {
     }
 
     let (rem, version) = openqasm_version(current).map_err(|_| {
-        QRustError::ParseError(
-            "Missing or invalid OPENQASM header. File must start with 'OPENQASM 2.0;'".into(),
-        )
+        // A miscased keyword is a common slip; say so rather than claiming
+        // the header is missing.
+        let miscased = current
+            .get(..8)
+            .is_some_and(|k| k != "OPENQASM" && k.eq_ignore_ascii_case("OPENQASM"));
+        let message = if miscased {
+            "OpenQASM header keyword must be uppercase 'OPENQASM'"
+        } else {
+            "Missing or invalid OPENQASM header. File must start with 'OPENQASM 2.0;'"
+        };
+        QRustError::Syntax(ParseError::at(input, current, message, 0))
     })?;
     if version != "2.0" {
         return Err(QRustError::Unsupported(format!(
@@ -88,8 +305,69 @@ pub fn parse_qasm(input: &str) -> Result<Circuit> {
             version
         )));
     }
-    current = rem;
+    parse_statements(
+        input,
+        rem,
+        &mut circuit,
+        &mut ctx,
+        &mut total_qubits,
+        &mut total_cbits,
+    )?;
 
+    if let Some(name) = ctx.open_regions.keys().min() {
+        return Err(QRustError::ParseError(format!(
+            "region '{}' is never closed with `// @region-end`",
+            name
+        )));
+    }
+    circuit.num_qubits = total_qubits;
+    circuit.num_cbits = total_cbits;
+    Ok(circuit)
+}
+
+/// Parses a source holding several OpenQASM 2.0 programs back to back,
+/// e.g. a benchmark collection stored in one file.
+///
+/// Every line starting with `OPENQASM` begins a new program; each program
+/// is parsed independently with [`parse_qasm`], so registers and gate
+/// definitions do not leak between them. Anything before the first header
+/// (such as comments) belongs to the first program.
+///
+/// # Errors
+/// Returns the first error from [`parse_qasm`]; an input with no header at
+/// all fails the same way a single program would.
+pub fn parse_qasm_multi(input: &str) -> Result<Vec<Circuit>> {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("OPENQASM") {
+            starts.push(offset + (line.len() - trimmed.len()));
+        }
+        offset += line.len();
+    }
+    if starts.len() <= 1 {
+        return parse_qasm(input).map(|c| vec![c]);
+    }
+    // Leading comments stay attached to the first program.
+    starts[0] = 0;
+    starts.push(input.len());
+    starts
+        .windows(2)
+        .map(|w| parse_qasm(&input[w[0]..w[1]]))
+        .collect()
+}
+
+/// Parses the statements of `source` from `current` (a suffix of it) to the
+/// end, feeding them to [`handle_statement`].
+fn parse_statements(
+    source: &str,
+    mut current: &str,
+    circuit: &mut Circuit,
+    ctx: &mut ParseContext,
+    total_qubits: &mut usize,
+    total_cbits: &mut usize,
+) -> Result<()> {
     loop {
         let (rem, _) = multispace0::<&str, nom::error::Error<&str>>(current)
             .map_err(|e| QRustError::ParseError(e.to_string()))?;
@@ -97,7 +375,12 @@ pub fn parse_qasm(input: &str) -> Result<Circuit> {
         if current.is_empty() {
             break;
         }
-        if let Ok((rem, _)) = comment(current) {
+        if let Ok((rem, stmt)) = rules::pragma(current) {
+            current = rem;
+            handle_statement(circuit, ctx, total_qubits, total_cbits, stmt)?;
+            continue;
+        }
+        if let Some(rem) = skip_comment(source, current)? {
             current = rem;
             continue;
         }
@@ -113,24 +396,63 @@ pub fn parse_qasm(input: &str) -> Result<Circuit> {
             rules::if_stmt,
             gate_call,
         ))(current)
-        .map_err(|_| {
-            let snippet: String = current.chars().take(60).collect();
-            QRustError::ParseError(format!("parse error near: {}", snippet))
-        })?;
+        .map_err(|_| QRustError::Syntax(ParseError::at(source, current, "parse error", 60)))?;
         current = rem;
 
-        handle_statement(
-            &mut circuit,
-            &mut ctx,
-            &mut total_qubits,
-            &mut total_cbits,
-            stmt,
-        )?;
+        handle_statement(circuit, ctx, total_qubits, total_cbits, stmt)?;
     }
+    Ok(())
+}
 
-    circuit.num_qubits = total_qubits;
-    circuit.num_cbits = total_cbits;
-    Ok(circuit)
+/// Resolves `include "filename";` against the context's search paths and
+/// parses the file's statements into `circuit`.
+fn include_file(
+    circuit: &mut Circuit,
+    ctx: &mut ParseContext,
+    total_qubits: &mut usize,
+    total_cbits: &mut usize,
+    filename: &str,
+) -> Result<()> {
+    let candidates: Vec<PathBuf> = ctx
+        .search_paths
+        .iter()
+        .map(|dir| dir.join(filename))
+        .collect();
+    let Some(path) = candidates.iter().find(|p| p.is_file()) else {
+        let tried: Vec<String> = candidates.iter().map(|p| p.display().to_string()).collect();
+        return Err(QRustError::ParseError(format!(
+            "include \"{}\" not found (tried {})",
+            filename,
+            tried.join(", ")
+        )));
+    };
+    let io_err = |path: &Path, e: std::io::Error| {
+        QRustError::ParseError(format!("cannot read include '{}': {}", path.display(), e))
+    };
+    let canonical = path.canonicalize().map_err(|e| io_err(path, e))?;
+    if let Some(pos) = ctx.include_stack.iter().position(|p| *p == canonical) {
+        let cycle: Vec<String> = ctx.include_stack[pos..]
+            .iter()
+            .chain([&canonical])
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(QRustError::ParseError(format!(
+            "include cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+    let source = std::fs::read_to_string(&canonical).map_err(|e| io_err(path, e))?;
+
+    ctx.include_stack.push(canonical);
+    let parsed = parse_statements(&source, &source, circuit, ctx, total_qubits, total_cbits);
+    ctx.include_stack.pop();
+    parsed.map_err(|e| match e {
+        QRustError::Syntax(mut located) => {
+            located.message = format!("{} in '{}'", located.message, path.display());
+            QRustError::Syntax(located)
+        }
+        other => other,
+    })
 }
 
 fn handle_statement(
@@ -142,24 +464,76 @@ fn handle_statement(
 ) -> Result<()> {
     match stmt {
         ParsedStatement::Ignore => {}
+        ParsedStatement::Block(_) => {
+            return Err(QRustError::ParseError(
+                "a braced block is only allowed as the body of an `if`".into(),
+            ));
+        }
+        ParsedStatement::Pragma(name, arg) => handle_pragma(circuit, ctx, &name, &arg)?,
         ParsedStatement::Include(filename) => {
-            if filename != "qelib1.inc" {
+            if filename == "qelib1.inc" {
+                ctx.qelib1 = true;
+            } else if ctx.search_paths.is_empty() {
                 return Err(QRustError::Unsupported(format!(
                     "Includes are not supported. Please resolve all imports before parsing. \
                      Found: 'include \"{}\"'",
                     filename
                 )));
+            } else {
+                include_file(circuit, ctx, total_qubits, total_cbits, &filename)?;
             }
         }
         ParsedStatement::QReg(name, size) => {
+            circuit
+                .qregs
+                .push(Register::new(name.clone(), *total_qubits, size));
             ctx.qregs.insert(name, (*total_qubits, size));
             *total_qubits += size;
         }
         ParsedStatement::CReg(name, size) => {
+            circuit
+                .cregs
+                .push(Register::new(name.clone(), *total_cbits, size));
             ctx.cregs.insert(name, (*total_cbits, size));
             *total_cbits += size;
         }
         ParsedStatement::GateDef(name, params, qubits, body) => {
+            if body
+                .iter()
+                .any(|stmt| matches!(stmt, ParsedStatement::Reset(_)))
+            {
+                return Err(QRustError::ParseError(format!(
+                    "reset is not allowed inside the body of gate '{name}': gate \
+                     definitions must be unitary"
+                )));
+            }
+            // Bind `// @param` constants now, so the body sees the values in
+            // effect at its definition. Formal parameters shadow them.
+            let body = if ctx.params.is_empty() {
+                body
+            } else {
+                let mut bindings = ctx.params.clone();
+                for p in &params {
+                    bindings.remove(p);
+                }
+                body.into_iter()
+                    .map(|stmt| match stmt {
+                        ParsedStatement::Gate(n, q, exprs) => ParsedStatement::Gate(
+                            n,
+                            q,
+                            exprs.iter().map(|e| e.substitute(&bindings)).collect(),
+                        ),
+                        other => other,
+                    })
+                    .collect()
+            };
+            if ctx.qelib1 {
+                for stmt in &body {
+                    if let ParsedStatement::Gate(callee, _, _) = stmt {
+                        register_qelib1_gate(circuit, callee);
+                    }
+                }
+            }
             circuit.register_custom_gate(
                 name.clone(),
                 params.clone(),
@@ -169,12 +543,10 @@ fn handle_statement(
             ctx.gate_defs.insert(name, (params, qubits, body));
         }
         ParsedStatement::Gate(name, qubits, params) => {
-            // Intercept the reset sentinel from rules::reset.
-            if name == "__reset__" {
-                emit_reset(circuit, ctx, &qubits, None)?;
-            } else {
-                emit_resolved_gate_call(circuit, ctx, &name, &qubits, &params, None)?;
-            }
+            emit_resolved_gate_call(circuit, ctx, &name, &qubits, &params, None)?;
+        }
+        ParsedStatement::Reset(qubits) => {
+            emit_reset(circuit, ctx, &qubits, None)?;
         }
         ParsedStatement::Measure((q_name, q_idx), (c_name, c_idx)) => {
             emit_measure(circuit, ctx, &q_name, q_idx, &c_name, c_idx, None)?;
@@ -193,11 +565,17 @@ fn handle_statement(
             circuit.add_op(Operation::Barrier { qubits });
         }
         ParsedStatement::If(creg, value, inner) => {
-            if !ctx.cregs.contains_key(&creg) {
+            let Some(&(_, width)) = ctx.cregs.get(&creg) else {
                 return Err(QRustError::Undefined(format!(
                     "Undefined classical register in `if`: {}",
                     creg
                 )));
+            };
+            // A value with bits above the register width can never match.
+            if width < usize::BITS as usize && value >> width != 0 {
+                return Err(QRustError::ParseError(format!(
+                    "`if` value {value} does not fit in {width}-bit classical register '{creg}'"
+                )));
             }
             let condition = ClassicalCondition {
                 creg,
@@ -205,18 +583,17 @@ fn handle_statement(
             };
             match *inner {
                 ParsedStatement::Gate(name, qubits, params) => {
-                    if name == "__reset__" {
-                        emit_reset(circuit, ctx, &qubits, Some(condition))?;
-                    } else {
-                        emit_resolved_gate_call(
-                            circuit,
-                            ctx,
-                            &name,
-                            &qubits,
-                            &params,
-                            Some(condition),
-                        )?;
-                    }
+                    emit_resolved_gate_call(
+                        circuit,
+                        ctx,
+                        &name,
+                        &qubits,
+                        &params,
+                        Some(condition),
+                    )?;
+                }
+                ParsedStatement::Reset(qubits) => {
+                    emit_reset(circuit, ctx, &qubits, Some(condition))?;
                 }
                 ParsedStatement::Measure((q_name, q_idx), (c_name, c_idx)) => {
                     emit_measure(
@@ -234,6 +611,39 @@ fn handle_statement(
                         "`if` over barrier is not supported".into(),
                     ));
                 }
+                ParsedStatement::Block(stmts) => {
+                    // Every statement is guarded by the same condition. Only
+                    // gates are allowed: a measurement could rewrite the
+                    // condition register mid-block, which per-op lowering
+                    // cannot express.
+                    for stmt in stmts {
+                        match stmt {
+                            ParsedStatement::Gate(name, qubits, params) => {
+                                emit_resolved_gate_call(
+                                    circuit,
+                                    ctx,
+                                    &name,
+                                    &qubits,
+                                    &params,
+                                    Some(condition.clone()),
+                                )?;
+                            }
+                            ParsedStatement::Reset(..) | ParsedStatement::Measure(..) => {
+                                return Err(QRustError::Unsupported(
+                                    "only unitary gates are allowed inside an `if { ... }` \
+                                     block; use a single-statement `if` for measure/reset"
+                                        .into(),
+                                ));
+                            }
+                            other => {
+                                return Err(QRustError::Unsupported(format!(
+                                    "`if` block over {:?} is not supported",
+                                    other
+                                )));
+                            }
+                        }
+                    }
+                }
                 other => {
                     return Err(QRustError::Unsupported(format!(
                         "`if` over {:?} is not supported",
@@ -246,6 +656,83 @@ fn handle_statement(
     Ok(())
 }
 
+/// Applies a `// @name argument` pragma. Unknown pragmas are ordinary
+/// comments and are ignored.
+fn handle_pragma(
+    circuit: &mut Circuit,
+    ctx: &mut ParseContext,
+    name: &str,
+    arg: &str,
+) -> Result<()> {
+    match name {
+        "gphase" => {
+            let phase = match rules::expr(arg) {
+                Ok(("", e)) => e.evaluate()?,
+                _ => {
+                    return Err(QRustError::ParseError(format!(
+                        "invalid expression in `// @gphase` pragma: '{}'",
+                        arg
+                    )))
+                }
+            };
+            circuit.global_phase += phase;
+        }
+        "param" => {
+            let (lhs, rhs) = arg.split_once('=').ok_or_else(|| {
+                QRustError::ParseError(format!("`// @param` expects `name = expr`, got '{}'", arg))
+            })?;
+            let pname = lhs.trim();
+            let valid_name = pname.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && pname.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_name || matches!(pname, "pi" | "tau" | "euler" | "e") {
+                return Err(QRustError::ParseError(format!(
+                    "invalid `// @param` name '{}'",
+                    pname
+                )));
+            }
+            let value = match rules::expr(rhs.trim()) {
+                Ok(("", e)) => e.evaluate_with_scope(&ctx.params)?,
+                _ => {
+                    return Err(QRustError::ParseError(format!(
+                        "invalid expression in `// @param` pragma: '{}'",
+                        rhs.trim()
+                    )))
+                }
+            };
+            ctx.params.insert(pname.to_string(), value);
+        }
+        "region-begin" => {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                return Err(QRustError::ParseError(format!(
+                    "`// @region-begin` expects a single name, got '{}'",
+                    arg
+                )));
+            }
+            if circuit.regions.contains_key(arg) || ctx.open_regions.contains_key(arg) {
+                return Err(QRustError::ParseError(format!(
+                    "region '{}' is declared twice",
+                    arg
+                )));
+            }
+            ctx.open_regions
+                .insert(arg.to_string(), circuit.operations.len());
+        }
+        "region-end" => {
+            let start = ctx.open_regions.remove(arg).ok_or_else(|| {
+                QRustError::ParseError(format!(
+                    "`// @region-end {}` without a matching `// @region-begin`",
+                    arg
+                ))
+            })?;
+            circuit
+                .regions
+                .insert(arg.to_string(), start..circuit.operations.len());
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 fn emit_reset(
     circuit: &mut Circuit,
     ctx: &ParseContext,
@@ -301,9 +788,19 @@ fn emit_measure(
         )));
     };
     if q_indices.len() != c_indices.len() {
-        return Err(QRustError::SizeMismatch(
-            "measure register size mismatch".into(),
-        ));
+        let operand = |name: &str, idx: Option<usize>| match idx {
+            Some(i) => format!("{name}[{i}]"),
+            None => name.to_string(),
+        };
+        return Err(QRustError::SizeMismatch(format!(
+            "measure {} -> {}: '{}' covers {} qubit(s) but '{}' covers {} bit(s)",
+            operand(q_name, q_idx),
+            operand(c_name, c_idx),
+            q_name,
+            q_indices.len(),
+            c_name,
+            c_indices.len()
+        )));
     }
     for (q, c) in q_indices.into_iter().zip(c_indices) {
         let op = Operation::Measure { qubit: q, cbit: c };
@@ -371,15 +868,21 @@ fn emit_gate(
     qubits: &[usize],
     condition: Option<ClassicalCondition>,
 ) -> Result<()> {
-    let empty_scope = HashMap::new();
     let mut eval_params = Vec::with_capacity(params.len());
     for p in params {
-        eval_params.push(p.evaluate_with_scope(&empty_scope)?);
+        eval_params.push(p.evaluate_with_scope(&ctx.params)?);
     }
 
-    let gate_type = name
-        .parse::<GateType>()
-        .unwrap_or_else(|_| GateType::Custom(name.to_string()));
+    let alias = ctx.aliases.get(name);
+    let gate_type = match alias {
+        Some(target) if target == name => GateType::Custom(name.to_string()),
+        _ => {
+            let name = alias.map_or(name, String::as_str);
+            name.parse::<GateType>()
+                .unwrap_or_else(|_| GateType::Custom(name.to_string()))
+        }
+    };
+    let name = alias.map_or(name, String::as_str);
 
     let emit = |circuit: &mut Circuit, op: Operation| match &condition {
         Some(cond) => circuit.add_op(Operation::Conditional {
@@ -406,7 +909,11 @@ fn emit_gate(
     }
 
     if let GateType::Custom(ref n) = gate_type {
-        if let Some((def_params, def_qubits, _)) = ctx.gate_defs.get(n) {
+        let def = ctx
+            .gate_defs
+            .get(n)
+            .or_else(|| ctx.qelib1.then(|| qelib1_defs().get(n)).flatten());
+        if let Some((def_params, def_qubits, _)) = def {
             if eval_params.len() != def_params.len() {
                 return Err(QRustError::ParseError(format!(
                     "gate '{}' expects {} params, got {}",
@@ -423,6 +930,20 @@ fn emit_gate(
                     qubits.len()
                 )));
             }
+            if ctx.qelib1 {
+                register_qelib1_gate(circuit, n);
+            }
+            emit(
+                circuit,
+                Operation::Gate {
+                    name: gate_type,
+                    qubits: qubits.to_vec(),
+                    params: eval_params,
+                },
+            );
+            return Ok(());
+        }
+        if ctx.lenient || alias.is_some() {
             emit(
                 circuit,
                 Operation::Gate {
@@ -441,6 +962,18 @@ fn emit_gate(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_qasm_multi() {
+        let src = "// suite\nOPENQASM 2.0;\nqreg q[1];\nh q[0];\nOPENQASM 2.0;\nqreg a[2];\ncreg c[2];\ncx a[0], a[1];\nmeasure a -> c;\n";
+        let circuits = parse_qasm_multi(src).unwrap();
+        assert_eq!(circuits.len(), 2);
+        assert_eq!(circuits[0].num_qubits, 1);
+        assert_eq!(circuits[0].operations.len(), 1);
+        assert_eq!(circuits[1].num_qubits, 2);
+        assert_eq!(circuits[1].num_cbits, 2);
+        assert_eq!(circuits[1].operations.len(), 3);
+    }
+
     #[test]
     fn test_standard_gates() {
         let qasm = r#"
@@ -480,13 +1013,26 @@ mod tests {
     #[test]
     fn test_missing_header() {
         let err = parse_qasm("qreg q[1];").unwrap_err();
-        assert!(matches!(err, QRustError::ParseError(_)));
+        assert!(matches!(err, QRustError::Syntax(_)));
+    }
+
+    #[test]
+    fn test_lowercase_header_gets_targeted_error() {
+        let err = parse_qasm("// lower\nopenqasm 2.0;\nqreg q[1];\n").unwrap_err();
+        let QRustError::Syntax(located) = &err else {
+            panic!("expected a syntax error, got {err:?}");
+        };
+        assert_eq!(
+            located.message,
+            "OpenQASM header keyword must be uppercase 'OPENQASM'"
+        );
+        assert_eq!((located.line, located.column), (2, 1));
     }
 
     #[test]
     fn test_garbage() {
         let err = parse_qasm("NOT A QASM FILE").unwrap_err();
-        assert!(matches!(err, QRustError::ParseError(_)));
+        assert!(matches!(err, QRustError::Syntax(_)));
     }
 
     #[test]
@@ -505,6 +1051,200 @@ mod tests {
             .any(|op| matches!(op, Operation::Barrier { .. })));
     }
 
+    #[test]
+    fn test_mixed_indexed_and_broadcast_barrier() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg a[2];
+            qreg b[3];
+            barrier a[1], b;
+            barrier b[2], a[0];
+        "#;
+        let c = parse_qasm(qasm).unwrap();
+        assert_eq!(
+            c.operations,
+            vec![
+                Operation::Barrier {
+                    qubits: vec![1, 2, 3, 4]
+                },
+                Operation::Barrier { qubits: vec![4, 0] },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bare_barrier_spans_all_declared_qubits() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg a[2];
+            qreg b[1];
+            h a[0];
+            barrier;
+            x b[0];
+        "#;
+        let c = parse_qasm(qasm).unwrap();
+        assert_eq!(
+            c.operations[1],
+            Operation::Barrier {
+                qubits: vec![0, 1, 2]
+            }
+        );
+        // The barrier synchronizes every wire, so `x b[0]` lands in layer 2.
+        assert_eq!(c.depth(), 2);
+    }
+
+    #[test]
+    fn test_gphase_pragma_sets_global_phase() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg q[1];
+            // @gphase pi/2
+            h q[0];
+        "#;
+        let c = parse_qasm(qasm).unwrap();
+        assert!((c.global_phase - PI / 2.0).abs() < 1e-12);
+        assert_eq!(c.operations.len(), 1);
+
+        let err = parse_qasm("OPENQASM 2.0; qreg q[1];\n// @gphase pi/\n").unwrap_err();
+        assert!(matches!(err, QRustError::ParseError(_)));
+    }
+
+    #[test]
+    fn test_aliases_rename_vendor_gates() {
+        let qasm = "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[2];\n\
+                    ecr q[0], q[1];\nsqrt_x q[1];\nrzx(0.5) q[0], q[1];\n";
+        let aliases: HashMap<String, String> =
+            [("ecr", "ecr"), ("sqrt_x", "sx"), ("rzx", "vendor_rzx")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        let circ = parse_qasm_with_aliases(qasm, &aliases).unwrap();
+        assert_eq!(
+            circ.operations[0],
+            Operation::Gate {
+                name: GateType::Custom("ecr".into()),
+                qubits: vec![0, 1],
+                params: vec![],
+            }
+        );
+        // `sx` comes from qelib1.inc, `vendor_rzx` is opaque.
+        assert_eq!(circ.operations[1].to_qasm(), "sx q[1];");
+        assert!(circ.custom_gates.get("sx").is_some());
+        assert_eq!(circ.operations[2].to_qasm(), "vendor_rzx(0.5) q[0], q[1];");
+
+        let plain = parse_qasm(qasm);
+        assert!(matches!(plain, Err(QRustError::UnknownGate(ref g)) if g == "sqrt_x"));
+    }
+
+    #[test]
+    fn test_lenient_mode_keeps_unknown_gates_for_drawing() {
+        let qasm = "OPENQASM 2.0;\nqreg q[3];\nh q[0];\nvendor_zx q[0], q[2];\n";
+        assert!(matches!(
+            parse_qasm(qasm),
+            Err(QRustError::UnknownGate(ref g)) if g == "vendor_zx"
+        ));
+
+        let circ = parse_qasm_lenient(qasm).unwrap();
+        assert_eq!(
+            circ.operations[1],
+            Operation::Gate {
+                name: GateType::Custom("vendor_zx".into()),
+                qubits: vec![0, 2],
+                params: vec![],
+            }
+        );
+        let drawing = circ.draw();
+        let rows: Vec<&str> = drawing.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].contains("vendor_zx"), "{drawing}");
+        assert!(rows[2].contains("vendor_zx"), "{drawing}");
+        assert!(rows[1].contains('│'), "{drawing}");
+        assert!(
+            rows[0].contains("─H─") && !rows[1].contains('H'),
+            "{drawing}"
+        );
+    }
+
+    #[test]
+    fn test_param_pragma_in_gate_body() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            // @param k = 0.5
+            // @param k2 = 2 * k
+            gate rot a { U(k,0,0) a; }
+            gate shadow(k) a { U(k,0,0) a; }
+            qreg q[1];
+            rot q[0];
+            shadow(0.25) q[0];
+            rx(k2) q[0];
+        "#;
+        let circ = parse_qasm(qasm).unwrap();
+        let flat = crate::transpiler::decomposition::try_unroll_custom_gates(&circ).unwrap();
+        let thetas: Vec<f64> = flat
+            .operations
+            .iter()
+            .map(|op| match op {
+                Operation::Gate { params, .. } => params[0],
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(thetas, [0.5, 0.25, 1.0]);
+
+        let bad = "OPENQASM 2.0;\n// @param = 1\n";
+        assert!(matches!(parse_qasm(bad), Err(QRustError::ParseError(_))));
+    }
+
+    #[test]
+    fn test_region_pragmas() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg q[2];
+            h q[0];
+            // @region-begin bell
+            h q[0];
+            cx q[0], q[1];
+            // @region-end bell
+            x q[1];
+        "#;
+        let circ = parse_qasm(qasm).unwrap();
+        assert_eq!(circ.regions["bell"], 1..3);
+        let bell = circ.region("bell").unwrap();
+        assert_eq!(bell.num_qubits, 2);
+        assert_eq!(bell.operations, circ.operations[1..3]);
+        assert!(circ.region("missing").is_none());
+
+        let unclosed = "OPENQASM 2.0;\nqreg q[1];\n// @region-begin a\nh q[0];\n";
+        assert!(matches!(
+            parse_qasm(unclosed),
+            Err(QRustError::ParseError(_))
+        ));
+        let stray = "OPENQASM 2.0;\nqreg q[1];\n// @region-end a\n";
+        assert!(matches!(parse_qasm(stray), Err(QRustError::ParseError(_))));
+    }
+
+    #[test]
+    fn test_conditional_reset() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg q[2];
+            creg c[1];
+            measure q[0] -> c[0];
+            if (c==1) reset q[0];
+        "#;
+        let circ = parse_qasm(qasm).unwrap();
+        assert!(circ.validate().is_empty());
+        assert_eq!(
+            circ.operations[1..],
+            [Operation::Conditional {
+                condition: ClassicalCondition {
+                    creg: "c".into(),
+                    value: 1,
+                },
+                op: Box::new(Operation::Reset { qubit: 0 }),
+            }]
+        );
+    }
+
     #[test]
     fn test_reset_parses() {
         let qasm = r#"
@@ -523,6 +1263,86 @@ mod tests {
         assert_eq!(resets, 3);
     }
 
+    #[test]
+    fn test_broadcast_measure_size_mismatch_names_registers() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg q[3];
+            creg c[2];
+            measure q -> c;
+        "#;
+        let err = parse_qasm(qasm).unwrap_err();
+        assert!(matches!(err, QRustError::SizeMismatch(_)), "{err}");
+        assert_eq!(
+            err.to_string(),
+            "Register size mismatch: measure q -> c: 'q' covers 3 qubit(s) but 'c' covers 2 bit(s)"
+        );
+
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg q[3];
+            creg c[3];
+            measure q -> c;
+        "#;
+        let c = parse_qasm(qasm).unwrap();
+        assert_eq!(
+            c.operations,
+            (0..3)
+                .map(|i| Operation::Measure { qubit: i, cbit: i })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_reset_rejected_in_gate_body() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            include "qelib1.inc";
+            qreg q[1];
+            gate prep a { reset a; h a; }
+            prep q[0];
+        "#;
+        let err = parse_qasm(qasm).unwrap_err();
+        assert!(
+            matches!(&err, QRustError::ParseError(msg) if msg.contains("reset") && msg.contains("prep")),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_conditional_value_must_fit_register() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            include "qelib1.inc";
+            qreg q[3];
+            creg c0[1];
+            creg c1[2];
+            if(c1==3) x q[2];
+            if(c0==1) z q[2];
+        "#;
+        let c = parse_qasm(qasm).unwrap();
+        assert_eq!(c.operations.len(), 2);
+
+        let qasm = r#"
+            OPENQASM 2.0;
+            include "qelib1.inc";
+            qreg q[1];
+            creg c[2];
+            if(c==4) x q[0];
+        "#;
+        let err = parse_qasm(qasm).unwrap_err();
+        assert!(
+            matches!(&err, QRustError::ParseError(m) if m.contains("2-bit classical register 'c'")),
+            "{err}"
+        );
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg q[1];
+            if(c==0) x q[0];
+        "#;
+        assert!(matches!(parse_qasm(qasm), Err(QRustError::Undefined(_))));
+    }
+
     #[test]
     fn test_conditional_gate() {
         let qasm = r#"
@@ -538,6 +1358,55 @@ mod tests {
             .any(|op| matches!(op, Operation::Conditional { .. })));
     }
 
+    #[test]
+    fn test_conditional_block() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg q[2];
+            creg c[1];
+            if (c==1) {
+                x q[0];
+                z q[1];
+            }
+            h q[0];
+        "#;
+        let circ = parse_qasm(qasm).unwrap();
+        assert_eq!(circ.operations.len(), 3);
+        for (op, (gate, qubit)) in circ.operations[..2]
+            .iter()
+            .zip([(GateType::X, 0), (GateType::Z, 1)])
+        {
+            match op {
+                Operation::Conditional { condition, op } => {
+                    assert_eq!(condition.creg, "c");
+                    assert_eq!(condition.value, 1);
+                    assert_eq!(
+                        **op,
+                        Operation::Gate {
+                            name: gate,
+                            qubits: vec![qubit],
+                            params: vec![],
+                        }
+                    );
+                }
+                other => panic!("expected conditional, got {other:?}"),
+            }
+        }
+        assert!(!circ.operations[2].is_conditional());
+    }
+
+    #[test]
+    fn test_conditional_block_rejects_measure() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            qreg q[1];
+            creg c[1];
+            if (c==1) { x q[0]; measure q[0] -> c[0]; }
+        "#;
+        let err = parse_qasm(qasm).unwrap_err();
+        assert!(matches!(err, QRustError::Unsupported(_)), "{err:?}");
+    }
+
     #[test]
     fn test_conditional_measure() {
         let qasm = r#"
@@ -3,12 +3,14 @@
 use crate::ir::ast::{Expr, ParsedStatement};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while, take_while1},
-    character::complete::{alpha1, alphanumeric1, char, digit1, space0, space1},
+    bytes::complete::{tag, take_until, take_while, take_while1},
+    character::complete::{
+        alpha1, alphanumeric1, char, digit1, multispace0, multispace1, space0, space1,
+    },
     combinator::{map, map_res, opt, recognize, value},
     multi::{many0, separated_list0},
     number::complete::recognize_float,
-    sequence::{delimited, pair, tuple},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
 
@@ -34,6 +36,38 @@ pub fn comment(input: &str) -> IResult<&str, ()> {
     value((), pair(tag("//"), take_while(|c| c != '\n')))(input)
 }
 
+/// Matches a `/* ... */` comment (which may span lines). Block comments do
+/// not nest.
+pub fn block_comment(input: &str) -> IResult<&str, ()> {
+    value((), tuple((tag("/*"), take_until("*/"), tag("*/"))))(input)
+}
+
+/// Skips any mix of whitespace (including newlines), `//` comments and
+/// `/* */` comments between the tokens of a statement.
+fn ws(input: &str) -> IResult<&str, ()> {
+    value(
+        (),
+        many0(alt((value((), multispace1), block_comment, comment))),
+    )(input)
+}
+
+/// Matches a `// @name argument...` pragma comment. The argument is the
+/// (trimmed) remainder of the line and may be empty.
+pub fn pragma(input: &str) -> IResult<&str, ParsedStatement> {
+    map(
+        tuple((
+            tag("//"),
+            space0,
+            char('@'),
+            take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+            take_while(|c| c != '\n'),
+        )),
+        |(_, _, _, name, arg): (_, _, _, &str, &str)| {
+            ParsedStatement::Pragma(name.to_string(), arg.trim().to_string())
+        },
+    )(input)
+}
+
 pub fn openqasm_version(input: &str) -> IResult<&str, String> {
     map(
         tuple((
@@ -94,19 +128,16 @@ fn qubit_ref(input: &str) -> IResult<&str, (String, Option<usize>)> {
     pair(
         identifier,
         opt(delimited(
-            tuple((space0, char('['), space0)),
+            tuple((ws, char('['), ws)),
             usize_parser,
-            tuple((space0, char(']'), space0)),
+            tuple((ws, char(']'), ws)),
         )),
     )(input)
 }
 
 fn term(input: &str) -> IResult<&str, Expr> {
     let (input, init) = factor(input)?;
-    let (input, res) = many0(pair(
-        delimited(space0, alt((char('*'), char('/'))), space0),
-        factor,
-    ))(input)?;
+    let (input, res) = many0(pair(delimited(ws, alt((char('*'), char('/'))), ws), factor))(input)?;
     Ok((
         input,
         res.into_iter().fold(init, |acc, (op, val)| match op {
@@ -117,28 +148,50 @@ fn term(input: &str) -> IResult<&str, Expr> {
     ))
 }
 
+/// A unary minus, or a [`power`]. Minus binds looser than `^`, so `-2^2`
+/// is `-(2^2)`.
 fn factor(input: &str) -> IResult<&str, Expr> {
     alt((
+        map(pair(delimited(ws, char('-'), ws), factor), |(_, f)| {
+            Expr::Sub(Box::new(Expr::Float(0.0)), Box::new(f))
+        }),
+        power,
+    ))(input)
+}
+
+/// `atom ^ factor`, binding tighter than `*` and `/`. Right-associative:
+/// `2^3^2` is `2^(3^2)` = 512. The exponent may carry a unary minus
+/// (`2^-1`).
+fn power(input: &str) -> IResult<&str, Expr> {
+    let (input, base) = atom(input)?;
+    let (input, exponent) = opt(preceded(delimited(ws, char('^'), ws), factor))(input)?;
+    Ok((
+        input,
+        match exponent {
+            Some(e) => Expr::Pow(Box::new(base), Box::new(e)),
+            None => base,
+        },
+    ))
+}
+
+fn atom(input: &str) -> IResult<&str, Expr> {
+    alt((
+        delimited(tuple((ws, char('('), ws)), expr, tuple((ws, char(')'), ws))),
+        map(parse_f64, Expr::Float),
         map(
-            pair(delimited(space0, char('-'), space0), factor),
-            |(_, f)| Expr::Sub(Box::new(Expr::Float(0.0)), Box::new(f)),
-        ),
-        delimited(
-            tuple((space0, char('('), space0)),
-            expr,
-            tuple((space0, char(')'), space0)),
+            pair(
+                identifier,
+                delimited(tuple((ws, char('('), ws)), expr, tuple((ws, char(')'), ws))),
+            ),
+            |(name, arg)| Expr::Call(name, Box::new(arg)),
         ),
-        map(parse_f64, Expr::Float),
         map(identifier, Expr::Var),
     ))(input)
 }
 
 pub fn expr(input: &str) -> IResult<&str, Expr> {
     let (input, init) = term(input)?;
-    let (input, res) = many0(pair(
-        delimited(space0, alt((char('+'), char('-'))), space0),
-        term,
-    ))(input)?;
+    let (input, res) = many0(pair(delimited(ws, alt((char('+'), char('-'))), ws), term))(input)?;
     Ok((
         input,
         res.into_iter().fold(init, |acc, (op, val)| match op {
@@ -152,21 +205,21 @@ pub fn expr(input: &str) -> IResult<&str, Expr> {
 pub fn gate_call(input: &str) -> IResult<&str, ParsedStatement> {
     let (input, name) = identifier(input)?;
     let (input, params) = opt(delimited(
-        tuple((space0, char('('), space0)),
-        separated_list0(tuple((space0, char(','), space0)), expr),
-        tuple((space0, char(')'), space0)),
+        tuple((space0, char('('), ws)),
+        separated_list0(tuple((ws, char(','), ws)), expr),
+        tuple((ws, char(')'), ws)),
     ))(input)?;
 
     let input = if params.is_some() {
-        let (input, _) = space0(input)?;
         input
     } else {
         let (input, _) = space1(input)?;
+        let (input, _) = ws(input)?;
         input
     };
 
-    let (input, qubits) = separated_list0(tuple((space0, char(','), space0)), qubit_ref)(input)?;
-    let (input, _) = space0(input)?;
+    let (input, qubits) = separated_list0(tuple((ws, char(','), ws)), qubit_ref)(input)?;
+    let (input, _) = ws(input)?;
     let (input, _) = tag(";")(input)?;
 
     Ok((
@@ -180,15 +233,16 @@ pub fn measure(input: &str) -> IResult<&str, ParsedStatement> {
         tuple((
             tag("measure"),
             space1,
+            ws,
             qubit_ref,
-            space0,
+            ws,
             tag("->"),
-            space0,
+            ws,
             qubit_ref,
-            space0,
+            ws,
             tag(";"),
         )),
-        |(_, _, q, _, _, _, c, _, _)| ParsedStatement::Measure(q, c),
+        |(_, _, _, q, _, _, _, c, _, _)| ParsedStatement::Measure(q, c),
     )(input)
 }
 
@@ -196,40 +250,27 @@ pub fn barrier(input: &str) -> IResult<&str, ParsedStatement> {
     map(
         tuple((
             tag("barrier"),
-            space0,
-            separated_list0(tuple((space0, char(','), space0)), qubit_ref),
-            space0,
+            ws,
+            separated_list0(tuple((ws, char(','), ws)), qubit_ref),
+            ws,
             tag(";"),
         )),
         |(_, _, qubits, _, _)| ParsedStatement::Barrier(qubits),
     )(input)
 }
 
-/// Parses `reset q[i];` or register-wide `reset q;`, producing a synthetic
-/// gate-call statement (the upper parser will expand it into
-/// `Operation::Reset` by intercepting the "reset" name).
-///
-/// We re-use `ParsedStatement::Gate` with the special name `"__reset__"` —
-/// but cleaner is to introduce a dedicated parser that the top-level parser
-/// dispatches on. Here we emit a `Gate("reset", ...)` call, which the
-/// existing parser machinery turns into an unknown gate error. To avoid
-/// churning the AST, we instead expose a dedicated helper.
+/// Parses `reset q[i];` or register-wide `reset q;`. The parser expands a
+/// register argument into one `Operation::Reset` per qubit.
 pub fn reset(input: &str) -> IResult<&str, ParsedStatement> {
     map(
         tuple((
             tag("reset"),
             space1,
-            separated_list0(tuple((space0, char(','), space0)), qubit_ref),
-            space0,
+            separated_list0(tuple((ws, char(','), ws)), qubit_ref),
+            ws,
             tag(";"),
         )),
-        |(_, _, qubits, _, _)| {
-            // Represent reset as a Gate with a sentinel name. The parser
-            // `handle_statement` must be aware of this — but to avoid
-            // touching the AST enum shape, we piggyback on Gate with a
-            // well-known sentinel identifier.
-            ParsedStatement::Gate("__reset__".to_string(), qubits, Vec::new())
-        },
+        |(_, _, qubits, _, _)| ParsedStatement::Reset(qubits),
     )(input)
 }
 
@@ -248,7 +289,7 @@ pub fn if_stmt(input: &str) -> IResult<&str, ParsedStatement> {
             space0,
             char(')'),
             space0,
-            alt((measure, reset, gate_call, barrier)),
+            alt((if_block, measure, reset, gate_call, barrier)),
         )),
         |(_, _, _, _, creg, _, _, _, val, _, _, _, op)| {
             ParsedStatement::If(creg, val, Box::new(op))
@@ -256,8 +297,27 @@ pub fn if_stmt(input: &str) -> IResult<&str, ParsedStatement> {
     )(input)
 }
 
+/// Braced body of a multi-statement `if (c==v) { ... }` (a dialect
+/// extension). Statements are accepted syntactically here; the parser
+/// rejects non-unitary ones when lowering.
+fn if_block(input: &str) -> IResult<&str, ParsedStatement> {
+    map(
+        delimited(
+            pair(char('{'), multispace0),
+            many0(terminated(
+                alt((measure, reset, gate_call, barrier)),
+                multispace0,
+            )),
+            char('}'),
+        ),
+        ParsedStatement::Block,
+    )(input)
+}
+
+/// `reset` is accepted here only so the parser can reject it with a clear
+/// message; QASM 2.0 gate bodies must be unitary.
 fn gate_body_stmt(input: &str) -> IResult<&str, ParsedStatement> {
-    alt((barrier, gate_call))(input)
+    alt((barrier, reset, gate_call))(input)
 }
 
 pub fn gate_def(input: &str) -> IResult<&str, ParsedStatement> {
@@ -271,12 +331,12 @@ pub fn gate_def(input: &str) -> IResult<&str, ParsedStatement> {
     ))(input)?;
     let (input, _) = space0(input)?;
     let (input, qubits) = separated_list0(tuple((space0, char(','), space0)), identifier)(input)?;
-    let (input, _) = space0(input)?;
 
+    // The body may span several lines and contain comments.
     let (input, body) = delimited(
-        tuple((space0, char('{'), space0)),
-        many0(delimited(space0, gate_body_stmt, space0)),
-        tuple((space0, char('}'), space0)),
+        tuple((ws, char('{'), ws)),
+        many0(delimited(ws, gate_body_stmt, ws)),
+        tuple((ws, char('}'), space0)),
     )(input)?;
 
     Ok((
@@ -1,10 +1,13 @@
-//! Unitary circuit simulator for verification.
+//! Circuit simulator: unitaries and state vectors for verification, plus
+//! shot-based sampling with mid-circuit measurement.
 
 use crate::error::{QRustError, Result};
-use crate::ir::{Circuit, GateDefinition, Operation};
+use crate::ir::registry::GateRegistry;
+use crate::ir::{Circuit, GateDefinition, GateType, Operation};
 use crate::SHIFT_GUARD;
 use nalgebra::{DMatrix, DVector};
 use num_complex::Complex;
+use std::collections::{BTreeMap, HashMap};
 
 type C = Complex<f64>;
 
@@ -113,6 +116,28 @@ fn embed_3q(gate_8x8: &DMatrix<C>, q0: usize, q1: usize, q2: usize, n_qubits: us
     full
 }
 
+/// Returns the local unitary and arity of a gate, preferring a
+/// matrix-defined custom gate from `registry` over the built-in definition.
+///
+/// # Errors
+/// Returns [`QRustError::Simulation`] for a custom gate with no matrix in
+/// `registry`; such gates must be unrolled first.
+fn local_unitary(
+    registry: &GateRegistry,
+    name: &GateType,
+    params: &[f64],
+) -> Result<(DMatrix<C>, usize)> {
+    if let GateType::Custom(custom_name) = name {
+        return match registry.unitary(custom_name) {
+            Some(m) => Ok((m.clone(), m.nrows().trailing_zeros() as usize)),
+            None => Err(QRustError::Simulation(format!(
+                "custom gate '{custom_name}' has no definition to simulate"
+            ))),
+        };
+    }
+    Ok((name.unitary(params), GateDefinition::num_qubits(name)))
+}
+
 pub fn try_circuit_to_unitary(circuit: &Circuit) -> Result<DMatrix<C>> {
     if circuit.num_qubits > MAX_QUBITS {
         return Err(QRustError::Simulation(format!(
@@ -139,8 +164,8 @@ pub fn try_circuit_to_unitary(circuit: &Circuit) -> Result<DMatrix<C>> {
             params,
         } = op
         {
-            let local_u = name.unitary(params);
-            let gate_u = match name.num_qubits() {
+            let (local_u, arity) = local_unitary(&unrolled.custom_gates, name, params)?;
+            let gate_u = match arity {
                 0 => continue,
                 1 => {
                     if qubits.is_empty() {
@@ -377,13 +402,14 @@ fn apply_3q_gate(
 
 fn apply_gate_to_state(
     state: &mut DVector<C>,
-    name: &crate::ir::GateType,
+    registry: &GateRegistry,
+    name: &GateType,
     qubits: &[usize],
     params: &[f64],
     n: usize,
 ) -> Result<()> {
-    let local_u = name.unitary(params);
-    match name.num_qubits() {
+    let (local_u, arity) = local_unitary(registry, name, params)?;
+    match arity {
         0 => Ok(()),
         1 => {
             if qubits.is_empty() || qubits[0] >= n {
@@ -460,13 +486,354 @@ pub fn evolve_state(circuit: &Circuit, init: &DVector<C>) -> Result<DVector<C>>
             params,
         } = op
         {
-            apply_gate_to_state(&mut state, name, qubits, params, n)?;
+            apply_gate_to_state(&mut state, &unrolled.custom_gates, name, qubits, params, n)?;
         }
     }
     Ok(state)
 }
 
+/// Runs `circuit` `shots` times with mid-circuit measurement, reset and
+/// classical control, returning how often each classical outcome occurred.
+///
+/// Keys are bitstrings over all `num_cbits` classical bits in the usual
+/// QASM order: `c[n-1]` first, `c[0]` last. Runs are reproducible for a
+/// given `seed`. Supports up to [`MAX_STATE_VECTOR_QUBITS`].
+pub fn sample(circuit: &Circuit, shots: usize, seed: u64) -> Result<HashMap<String, usize>> {
+    sample_with_creg_init(circuit, shots, seed, &HashMap::new())
+}
+
+/// Like [`sample`], but every shot starts with the classical registers in
+/// `init` preloaded (register name → value, bit `i` of the value into the
+/// register's bit `i`), e.g. to drive a conditional branch directly.
+///
+/// # Errors
+/// Returns [`QRustError::Undefined`] for a register the circuit does not
+/// declare and [`QRustError::InvalidConfig`] for a value that does not fit
+/// its register, besides the usual simulation errors.
+pub fn sample_with_creg_init(
+    circuit: &Circuit,
+    shots: usize,
+    seed: u64,
+    init: &HashMap<String, u64>,
+) -> Result<HashMap<String, usize>> {
+    let n = circuit.num_qubits;
+    if n > MAX_STATE_VECTOR_QUBITS || n >= SHIFT_GUARD {
+        return Err(QRustError::Simulation(format!(
+            "sample: {n} qubits exceeds practical limit ({MAX_STATE_VECTOR_QUBITS})"
+        )));
+    }
+    let mut initial_bits = vec![false; circuit.num_cbits];
+    for (name, &value) in init {
+        let bits = circuit
+            .creg_bits(name)
+            .ok_or_else(|| QRustError::Undefined(format!("classical register '{name}'")))?;
+        if bits.len() < 64 && value >> bits.len() != 0 {
+            return Err(QRustError::InvalidConfig(format!(
+                "initial value {value} does not fit in {}-bit register '{name}'",
+                bits.len()
+            )));
+        }
+        for (i, b) in bits.enumerate() {
+            initial_bits[b] = (value >> i) & 1 == 1;
+        }
+    }
+
+    let unrolled = crate::transpiler::decomposition::try_unroll_custom_gates(circuit)
+        .map_err(|e| QRustError::Simulation(format!("custom-gate unroll failed: {e}")))?;
+    let mut zero = DVector::<C>::zeros(1usize << n);
+    zero[0] = C::new(1.0, 0.0);
+
+    let mut rng = SplitMix64::new(seed);
+    let mut counts = HashMap::new();
+    for _ in 0..shots {
+        let mut state = zero.clone();
+        let mut cbits = initial_bits.clone();
+        for op in &unrolled.operations {
+            run_shot_op(&unrolled, op, &mut state, &mut cbits, &mut rng)?;
+        }
+        let key: String = cbits
+            .iter()
+            .rev()
+            .map(|&b| if b { '1' } else { '0' })
+            .collect();
+        *counts.entry(key).or_insert(0) += 1;
+    }
+    Ok(counts)
+}
+
+/// Expectation value `⟨ψ|P|ψ⟩` of the Pauli string `pauli` in the state
+/// `|ψ⟩` the circuit prepares from `|0…0⟩`.
+///
+/// `pauli` has one of `I`, `X`, `Y`, `Z` per qubit, with the **last**
+/// character acting on qubit 0 — the same order as the bitstrings from
+/// [`sample`]. Barriers are ignored.
+///
+/// # Errors
+/// - [`QRustError::InvalidConfig`] if `pauli` is not `num_qubits` Pauli
+///   letters.
+/// - [`QRustError::Unsupported`] if the circuit measures, resets or has
+///   classically-conditioned operations; the state would not be pure.
+/// - Any error from [`evolve_state`].
+pub fn expectation(circuit: &Circuit, pauli: &str) -> Result<f64> {
+    let n = circuit.num_qubits;
+    if pauli.chars().count() != n {
+        return Err(QRustError::InvalidConfig(format!(
+            "Pauli string '{pauli}' has {} letters but the circuit has {n} qubits",
+            pauli.chars().count()
+        )));
+    }
+    if let Some(op) = circuit.operations.iter().find(|op| {
+        matches!(
+            op,
+            Operation::Measure { .. } | Operation::Reset { .. } | Operation::Conditional { .. }
+        )
+    }) {
+        return Err(QRustError::Unsupported(format!(
+            "expectation values need a unitary circuit, found '{op}'"
+        )));
+    }
+
+    if n > MAX_STATE_VECTOR_QUBITS {
+        return Err(QRustError::Simulation(format!(
+            "expectation: {n} qubits exceeds practical limit ({MAX_STATE_VECTOR_QUBITS})"
+        )));
+    }
+
+    // Qubit q is character n-1-q; collect the X-flip, Z-sign and Y masks.
+    let (mut x_mask, mut z_mask, mut y_count) = (0usize, 0usize, 0u32);
+    for (q, ch) in pauli.chars().rev().enumerate() {
+        match ch.to_ascii_uppercase() {
+            'I' => {}
+            'X' => x_mask |= 1 << q,
+            'Z' => z_mask |= 1 << q,
+            'Y' => {
+                x_mask |= 1 << q;
+                z_mask |= 1 << q;
+                y_count += 1;
+            }
+            other => {
+                return Err(QRustError::InvalidConfig(format!(
+                    "invalid Pauli letter '{other}' in '{pauli}' (expected I, X, Y or Z)"
+                )))
+            }
+        }
+    }
+
+    let mut init = DVector::<C>::zeros(1usize << n);
+    init[0] = C::new(1.0, 0.0);
+    let psi = evolve_state(circuit, &init)?;
+    // P|i⟩ = i^{#Y} (-1)^{popcount(i & z)} |i ^ x⟩, using Y = i·X·Z.
+    let y_phase = C::i().powu(y_count);
+    let value: C = psi
+        .iter()
+        .enumerate()
+        .map(|(i, amp)| {
+            let sign = if (i & z_mask).count_ones() % 2 == 1 {
+                -1.0
+            } else {
+                1.0
+            };
+            psi[i ^ x_mask].conj() * y_phase * sign * amp
+        })
+        .sum();
+    Ok(value.re)
+}
+
+/// Renders sampled counts as a JSON object, e.g. `{"00":51,"11":49}`, with
+/// bitstring keys in sorted order so the output is deterministic.
+pub fn counts_to_json(counts: &HashMap<String, usize>) -> String {
+    let sorted: BTreeMap<&String, &usize> = counts.iter().collect();
+    // Serializing string keys and integers cannot fail.
+    serde_json::to_string(&sorted).unwrap_or_default()
+}
+
+/// Renders sampled counts as CSV with a `bitstring,count` header and one
+/// row per outcome, sorted by bitstring.
+pub fn counts_to_csv(counts: &HashMap<String, usize>) -> String {
+    let sorted: BTreeMap<&String, &usize> = counts.iter().collect();
+    let mut csv = String::from("bitstring,count\n");
+    for (bits, n) in sorted {
+        csv.push_str(&format!("{bits},{n}\n"));
+    }
+    csv
+}
+
+/// Layer-by-layer state-vector simulation, for stepping through a circuit
+/// and inspecting the intermediate state.
+///
+/// Each [`StatevectorSim::step`] applies one layer of [`Circuit::layers`]
+/// (of the circuit with custom gates unrolled). Measurements and resets
+/// collapse the state using a PRNG seeded at construction, and
+/// conditionals read the classical bits written so far.
+///
+/// # Example
+/// ```
+/// use q_rust::ir::{Circuit, GateType, Operation};
+/// use q_rust::simulator::StatevectorSim;
+///
+/// let mut c = Circuit::new(1, 0);
+/// c.add_op(Operation::Gate { name: GateType::X, qubits: vec![0], params: vec![] });
+/// let mut sim = StatevectorSim::new(&c, 0).unwrap();
+/// while sim.step().unwrap() {}
+/// assert!((sim.state()[1].re - 1.0).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StatevectorSim {
+    circuit: Circuit,
+    layers: Vec<Vec<usize>>,
+    next_layer: usize,
+    state: DVector<C>,
+    cbits: Vec<bool>,
+    rng: SplitMix64,
+}
+
+impl StatevectorSim {
+    /// Prepares `circuit` for stepping from `|0…0⟩` with all classical bits
+    /// cleared. `seed` drives measurement outcomes.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::Simulation`] if the circuit is wider than
+    /// [`MAX_STATE_VECTOR_QUBITS`] or a custom gate cannot be unrolled.
+    pub fn new(circuit: &Circuit, seed: u64) -> Result<Self> {
+        let n = circuit.num_qubits;
+        if n > MAX_STATE_VECTOR_QUBITS || n >= SHIFT_GUARD {
+            return Err(QRustError::Simulation(format!(
+                "StatevectorSim: {n} qubits exceeds practical limit ({MAX_STATE_VECTOR_QUBITS})"
+            )));
+        }
+        let circuit = crate::transpiler::decomposition::try_unroll_custom_gates(circuit)
+            .map_err(|e| QRustError::Simulation(format!("custom-gate unroll failed: {e}")))?;
+        let mut state = DVector::<C>::zeros(1usize << n);
+        state[0] = C::new(1.0, 0.0);
+        Ok(Self {
+            layers: circuit.layers(),
+            cbits: vec![false; circuit.num_cbits],
+            circuit,
+            next_layer: 0,
+            state,
+            rng: SplitMix64::new(seed),
+        })
+    }
+
+    /// Applies the next layer. Returns `false`, leaving the state unchanged,
+    /// once every layer has been applied.
+    pub fn step(&mut self) -> Result<bool> {
+        let Some(layer) = self.layers.get(self.next_layer) else {
+            return Ok(false);
+        };
+        for &i in layer {
+            run_shot_op(
+                &self.circuit,
+                &self.circuit.operations[i],
+                &mut self.state,
+                &mut self.cbits,
+                &mut self.rng,
+            )?;
+        }
+        self.next_layer += 1;
+        Ok(true)
+    }
+
+    /// The current amplitude vector (qubit `q` is bit `q` of the index).
+    pub fn state(&self) -> &DVector<C> {
+        &self.state
+    }
+
+    /// The classical bits written so far, indexed by cbit.
+    pub fn cbits(&self) -> &[bool] {
+        &self.cbits
+    }
+
+    /// Number of layers applied so far.
+    pub fn layers_applied(&self) -> usize {
+        self.next_layer
+    }
+
+    /// Total number of layers.
+    pub fn num_layers(&self) -> usize {
+        self.layers.len()
+    }
+}
+
+/// Applies one operation of a single shot of [`sample_with_creg_init`].
+fn run_shot_op(
+    circuit: &Circuit,
+    op: &Operation,
+    state: &mut DVector<C>,
+    cbits: &mut [bool],
+    rng: &mut SplitMix64,
+) -> Result<()> {
+    let n = circuit.num_qubits;
+    match op {
+        Operation::Gate {
+            name,
+            qubits,
+            params,
+        } => apply_gate_to_state(state, &circuit.custom_gates, name, qubits, params, n),
+        Operation::Measure { qubit, cbit } => {
+            if *cbit >= cbits.len() {
+                return Err(QRustError::Simulation(format!(
+                    "measure into cbit {cbit} of a {}-bit circuit",
+                    cbits.len()
+                )));
+            }
+            cbits[*cbit] = measure_qubit(state, *qubit, n, rng)?;
+            Ok(())
+        }
+        Operation::Reset { qubit } => {
+            if measure_qubit(state, *qubit, n, rng)? {
+                let x = GateType::X.unitary(&[]);
+                apply_1q_gate(state, &x, *qubit, n);
+            }
+            Ok(())
+        }
+        Operation::Barrier { .. } => Ok(()),
+        Operation::Conditional { condition, op } => {
+            // An unknown register reads every bit, as in the DAG builder.
+            let bits = circuit.creg_bits(&condition.creg).unwrap_or(0..cbits.len());
+            let value = bits
+                .enumerate()
+                .filter(|&(_, b)| cbits.get(b).copied().unwrap_or(false))
+                .fold(0u64, |acc, (i, _)| {
+                    acc | 1u64.checked_shl(i as u32).unwrap_or(0)
+                });
+            if value == condition.value {
+                run_shot_op(circuit, op, state, cbits, rng)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Projectively measures `q` in the computational basis, collapsing and
+/// renormalizing `state`. Returns the outcome.
+fn measure_qubit(state: &mut DVector<C>, q: usize, n: usize, rng: &mut SplitMix64) -> Result<bool> {
+    if q >= n {
+        return Err(QRustError::Simulation(format!(
+            "invalid measurement target (n={n}, qubit={q})"
+        )));
+    }
+    let mask = 1usize << q;
+    let p1: f64 = state
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i & mask != 0)
+        .map(|(_, a)| a.norm_sqr())
+        .sum();
+    let outcome = rng.next_unit() < p1;
+    let norm = if outcome { p1 } else { 1.0 - p1 }.sqrt();
+    for (i, a) in state.iter_mut().enumerate() {
+        if (i & mask != 0) == outcome {
+            *a /= C::new(norm, 0.0);
+        } else {
+            *a = C::new(0.0, 0.0);
+        }
+    }
+    Ok(outcome)
+}
+
 /// Splitmix64 — simple, fast, good-quality PRNG with full 64-bit state.
+#[derive(Debug, Clone)]
 struct SplitMix64(u64);
 impl SplitMix64 {
     fn new(seed: u64) -> Self {
@@ -650,6 +1017,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_custom_unitary_matches_builtin_h() {
+        let r = std::f64::consts::FRAC_1_SQRT_2;
+        let h = DMatrix::from_row_slice(2, 2, &[c(r, 0.0), c(r, 0.0), c(r, 0.0), c(-r, 0.0)]);
+        let mut custom = Circuit::new(2, 0);
+        custom
+            .register_custom_unitary("my_h".into(), h)
+            .expect("H is unitary");
+        custom.add_op(Operation::Gate {
+            name: GateType::Custom("my_h".into()),
+            qubits: vec![1],
+            params: vec![],
+        });
+        let mut builtin = Circuit::new(2, 0);
+        builtin.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![1],
+            params: vec![],
+        });
+        let fid = unitary_fidelity(&circuit_to_unitary(&custom), &circuit_to_unitary(&builtin));
+        assert!((fid - 1.0).abs() < 1e-10, "fidelity = {fid}");
+
+        let mut zero = DVector::<C>::zeros(4);
+        zero[0] = c(1.0, 0.0);
+        let a = evolve_state(&custom, &zero).unwrap();
+        let b = evolve_state(&builtin, &zero).unwrap();
+        assert!((a - b).norm() < 1e-10);
+    }
+
+    #[test]
+    fn test_custom_unitary_rejects_invalid_matrix() {
+        let mut circ = Circuit::new(1, 0);
+        let non_square = DMatrix::<C>::zeros(2, 3);
+        assert!(matches!(
+            circ.register_custom_unitary("bad".into(), non_square),
+            Err(QRustError::SizeMismatch(_))
+        ));
+        let three = DMatrix::<C>::identity(3, 3);
+        assert!(circ.register_custom_unitary("bad".into(), three).is_err());
+        let non_unitary = DMatrix::from_element(2, 2, c(1.0, 0.0));
+        assert!(matches!(
+            circ.register_custom_unitary("bad".into(), non_unitary),
+            Err(QRustError::InvalidConfig(_))
+        ));
+    }
+
     /// Same missing-gate contract for `evolve_state`.
     #[test]
     fn test_evolve_state_surfaces_missing_custom_gate() {
@@ -857,4 +1270,234 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_sample_with_creg_init_drives_conditional() {
+        use crate::ir::ClassicalCondition;
+        let mut c = Circuit::new(1, 1);
+        c.add_op(Operation::Conditional {
+            condition: ClassicalCondition {
+                creg: "c".into(),
+                value: 1,
+            },
+            op: Box::new(Operation::Gate {
+                name: GateType::X,
+                qubits: vec![0],
+                params: vec![],
+            }),
+        });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+
+        let counts = sample(&c, 20, QRUST_SEED).unwrap();
+        assert_eq!(counts.get("0"), Some(&20));
+
+        let init = HashMap::from([("c".to_string(), 1)]);
+        let counts = sample_with_creg_init(&c, 20, QRUST_SEED, &init).unwrap();
+        assert_eq!(counts.get("1"), Some(&20));
+
+        let bad = HashMap::from([("c".to_string(), 2)]);
+        assert!(matches!(
+            sample_with_creg_init(&c, 1, QRUST_SEED, &bad),
+            Err(QRustError::InvalidConfig(_))
+        ));
+        let unknown = HashMap::from([("d".to_string(), 0)]);
+        assert!(matches!(
+            sample_with_creg_init(&c, 1, QRUST_SEED, &unknown),
+            Err(QRustError::Undefined(_))
+        ));
+    }
+
+    #[test]
+    fn test_sample_reads_conditions_per_register() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[2];\ncreg a[1];\ncreg b[1];\n\
+             x q[0];\nmeasure q[0] -> a[0];\nif(b==0) x q[1];\nmeasure q[1] -> b[0];\n",
+        )
+        .unwrap();
+        let counts = sample(&c, 10, QRUST_SEED).unwrap();
+        assert_eq!(counts, HashMap::from([("11".to_string(), 10)]));
+    }
+
+    #[test]
+    fn test_sample_unrolls_custom_gates_under_conditionals() {
+        // `sx` is a qelib1.inc custom gate; under the condition it must
+        // still act, turning the deterministic 1 into a coin flip.
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[1];\ncreg c[1];\nx q[0];\n\
+             measure q[0] -> c[0];\nif(c==1) sx q[0];\nmeasure q[0] -> c[0];\n",
+        )
+        .unwrap();
+        let counts = sample(&c, 400, QRUST_SEED).unwrap();
+        let zeros = counts.get("0").copied().unwrap_or(0);
+        assert!((120..=280).contains(&zeros), "{counts:?}");
+
+        // An opaque custom gate is an error, not an identity.
+        let mut opaque = Circuit::new(1, 1);
+        opaque.add_op(Operation::Conditional {
+            condition: crate::ir::ClassicalCondition {
+                creg: "c".into(),
+                value: 0,
+            },
+            op: Box::new(Operation::Gate {
+                name: GateType::Custom("phantom_gate".into()),
+                qubits: vec![0],
+                params: vec![],
+            }),
+        });
+        assert!(matches!(
+            sample(&opaque, 1, QRUST_SEED),
+            Err(QRustError::Simulation(_))
+        ));
+        let mut psi = DVector::<C>::zeros(2);
+        psi[0] = C::new(1.0, 0.0);
+        assert!(apply_gate_to_state(
+            &mut psi,
+            &GateRegistry::new(),
+            &GateType::Custom("phantom_gate".into()),
+            &[0],
+            &[],
+            1
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_statevector_sim_steps_bell_layers() {
+        let mut bell = Circuit::new(2, 0);
+        bell.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        bell.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        let mut sim = StatevectorSim::new(&bell, QRUST_SEED).unwrap();
+        assert_eq!(sim.num_layers(), 2);
+
+        assert!(sim.step().unwrap());
+        // After H on q0: (|00⟩ + |01⟩)/√2, i.e. indices 0 and 1.
+        let expected = [c(h, 0.0), c(h, 0.0), c(0.0, 0.0), c(0.0, 0.0)];
+        for (a, e) in sim.state().iter().zip(expected) {
+            assert!((a - e).norm() < 1e-12, "{}", sim.state());
+        }
+
+        assert!(sim.step().unwrap());
+        let expected = [c(h, 0.0), c(0.0, 0.0), c(0.0, 0.0), c(h, 0.0)];
+        for (a, e) in sim.state().iter().zip(expected) {
+            assert!((a - e).norm() < 1e-12, "{}", sim.state());
+        }
+
+        assert!(!sim.step().unwrap());
+        assert_eq!(sim.layers_applied(), 2);
+    }
+
+    #[test]
+    fn test_statevector_sim_conditions_on_two_registers() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[2];\ncreg a[1];\ncreg b[1];\n\
+             x q[0];\nmeasure q[0] -> a[0];\nif(b==0) x q[1];\nif(a==0) x q[0];\n",
+        )
+        .unwrap();
+        let mut sim = StatevectorSim::new(&c, QRUST_SEED).unwrap();
+        while sim.step().unwrap() {}
+        // `b` is still 0, so q1 flips; `a` read 1, so q0 stays set.
+        assert!((sim.state()[3].re - 1.0).abs() < 1e-12, "{}", sim.state());
+        assert_eq!(sim.cbits(), &[true, false]);
+    }
+
+    #[test]
+    fn test_expectation_bell_state() {
+        let mut bell = Circuit::new(2, 1);
+        bell.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        bell.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        for (pauli, want) in [
+            ("ZZ", 1.0),
+            ("XX", 1.0),
+            ("YY", -1.0),
+            ("ZI", 0.0),
+            ("IX", 0.0),
+            ("II", 1.0),
+        ] {
+            let got = expectation(&bell, pauli).unwrap();
+            assert!((got - want).abs() < 1e-12, "<{pauli}> = {got}");
+        }
+
+        // |1⟩ on qubit 0 only: the last letter acts on qubit 0.
+        let mut x0 = Circuit::new(2, 0);
+        x0.add_op(Operation::Gate {
+            name: GateType::X,
+            qubits: vec![0],
+            params: vec![],
+        });
+        assert!((expectation(&x0, "IZ").unwrap() + 1.0).abs() < 1e-12);
+        assert!((expectation(&x0, "ZI").unwrap() - 1.0).abs() < 1e-12);
+
+        assert!(matches!(
+            expectation(&bell, "ZZZ"),
+            Err(QRustError::InvalidConfig(_))
+        ));
+        // Too wide to simulate: an error, not a mask-shift overflow.
+        assert!(matches!(
+            expectation(&Circuit::new(70, 0), &"X".repeat(70)),
+            Err(QRustError::Simulation(_))
+        ));
+        assert!(matches!(
+            expectation(&bell, "ZQ"),
+            Err(QRustError::InvalidConfig(_))
+        ));
+        bell.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        assert!(matches!(
+            expectation(&bell, "ZZ"),
+            Err(QRustError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_counts_export_is_sorted() {
+        let counts = HashMap::from([
+            ("11".to_string(), 49),
+            ("00".to_string(), 51),
+            ("01".to_string(), 0),
+        ]);
+        assert_eq!(counts_to_json(&counts), r#"{"00":51,"01":0,"11":49}"#);
+        assert_eq!(
+            counts_to_csv(&counts),
+            "bitstring,count\n00,51\n01,0\n11,49\n"
+        );
+        assert_eq!(counts_to_json(&HashMap::new()), "{}");
+        assert_eq!(counts_to_csv(&HashMap::new()), "bitstring,count\n");
+    }
+
+    #[test]
+    fn test_sample_bell_pair_is_correlated() {
+        let mut c = Circuit::new(2, 2);
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Measure { qubit: 1, cbit: 1 });
+        let counts = sample(&c, 200, QRUST_SEED).unwrap();
+        assert_eq!(counts.values().sum::<usize>(), 200);
+        assert!(counts.keys().all(|k| k == "00" || k == "11"), "{counts:?}");
+        assert!(counts.len() == 2, "{counts:?}");
+    }
 }
@@ -0,0 +1,129 @@
+//! Helpers for writing transpiler pass and circuit tests.
+//!
+//! Always compiled for the crate's own tests; downstream crates enable the
+//! `test-utils` feature to use them.
+
+use crate::ir::Circuit;
+use crate::parser::parse_qasm;
+use crate::transpiler::pass::Pass;
+use crate::transpiler::property_set::PropertySet;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Parses `input_qasm`, runs `pass` on it with a fresh [`PropertySet`] and
+/// asserts that the result has exactly `expected_ops` operations. Returns
+/// the transformed circuit for further checks.
+///
+/// # Panics
+/// If the source does not parse, or if the operation count differs; the
+/// message then lists every gate whose count changed, as `name: before ->
+/// after`.
+#[track_caller]
+pub fn assert_reduces(pass: &dyn Pass, input_qasm: &str, expected_ops: usize) -> Circuit {
+    let input = parse_qasm(input_qasm).unwrap_or_else(|e| panic!("input does not parse: {e}"));
+    let output = pass.run(&input, &mut PropertySet::new());
+    let actual = output.operations.len();
+    if actual != expected_ops {
+        let sorted =
+            |c: &Circuit| -> BTreeMap<String, usize> { c.gate_counts().into_iter().collect() };
+        let (before, after) = (sorted(&input), sorted(&output));
+        let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        let mut diff = String::new();
+        for name in names {
+            let b = before.get(name).copied().unwrap_or(0);
+            let a = after.get(name).copied().unwrap_or(0);
+            if b != a {
+                diff.push_str(&format!("\n  {name}: {b} -> {a}"));
+            }
+        }
+        panic!(
+            "{} left {} operation(s), expected {} (input had {}); gate counts:{}",
+            pass.name(),
+            actual,
+            expected_ops,
+            input.operations.len(),
+            if diff.is_empty() { " unchanged" } else { &diff }
+        );
+    }
+    output
+}
+
+/// Asserts that `b` undoes `a`: running `a` then `b` implements the
+/// identity up to global phase, checked with [`Circuit::verify_against`].
+/// Meant for small circuits, e.g. checking uncompute logic or
+/// [`Circuit::inverse`].
+///
+/// # Panics
+/// If the circuits have different qubit counts, if `b` cannot be appended
+/// to `a`, or if the product is not the identity.
+#[track_caller]
+pub fn assert_is_inverse(a: &Circuit, b: &Circuit) {
+    assert_eq!(
+        a.num_qubits, b.num_qubits,
+        "circuits act on different numbers of qubits"
+    );
+    let mut product = a.clone();
+    let qubits: Vec<usize> = (0..b.num_qubits).collect();
+    let cbits: Vec<usize> = (0..b.num_cbits).collect();
+    product.num_cbits = product.num_cbits.max(b.num_cbits);
+    product
+        .compose(b, &qubits, &cbits)
+        .unwrap_or_else(|e| panic!("cannot append the second circuit: {e}"));
+    let identity = Circuit::new(a.num_qubits, 0);
+    if let Err(e) = product.verify_against(&identity, 1e-9) {
+        panic!("second circuit does not invert the first: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transpiler::optimization::ParameterSimplificationPass;
+
+    const SRC: &str = "OPENQASM 2.0;\nqreg q[2];\nrx(0) q[0];\nh q[0];\nrz(2*pi) q[1];\n\
+                       cx q[0], q[1];\nU(0,0,0) q[1];\n";
+
+    #[test]
+    fn test_assert_reduces_passes_on_expected_count() {
+        let out = assert_reduces(&ParameterSimplificationPass::default(), SRC, 2);
+        assert_eq!(out.operations[0].to_qasm(), "h q[0];");
+    }
+
+    #[test]
+    fn test_assert_reduces_reports_gate_count_diff() {
+        let err = std::panic::catch_unwind(|| {
+            assert_reduces(&ParameterSimplificationPass::default(), SRC, 3)
+        })
+        .unwrap_err();
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert!(msg.contains("left 2 operation(s), expected 3"), "{msg}");
+        assert!(msg.contains("rx: 1 -> 0"), "{msg}");
+        assert!(msg.contains("rz: 1 -> 0"), "{msg}");
+        assert!(msg.contains("u: 1 -> 0"), "{msg}");
+        assert!(!msg.contains("h: "), "{msg}");
+    }
+
+    #[test]
+    fn test_assert_is_inverse() {
+        for src in [
+            "h q[0];\ncx q[0], q[1];\n",
+            "t q[0];\ns q[1];\nrzz(0.3) q[0], q[1];\nu(0.1, 0.2, 0.3) q[1];\n",
+            "ccx q[0], q[1], q[2];\nry(1.2) q[2];\nswap q[0], q[2];\n",
+        ] {
+            let a = parse_qasm(&format!(
+                "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[3];\n{src}"
+            ))
+            .unwrap();
+            assert_is_inverse(&a, &a.inverse().unwrap());
+            assert_is_inverse(&a.inverse().unwrap(), &a);
+        }
+    }
+
+    #[test]
+    fn test_assert_is_inverse_rejects_non_inverse() {
+        let a =
+            parse_qasm("OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[1];\ns q[0];\n").unwrap();
+        let err = std::panic::catch_unwind(|| assert_is_inverse(&a, &a)).unwrap_err();
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert!(msg.contains("does not invert"), "{msg}");
+    }
+}
@@ -3,9 +3,15 @@
 //! Each node is either an operation ([`DAGNode::Op`]) or an I/O terminal.
 //! Edges carry the [`Wire`] they route (a qubit or a classical bit), which
 //! makes commutation and local-rewrite analyses O(1) on adjacency.
+//!
+//! Classical data flow is modelled too: a measurement writes its cbit wire,
+//! and a conditional reads every cbit of the register it tests, so it
+//! depends on the measurements that produced them.
 
-use crate::ir::{Circuit, Operation};
+use crate::ir::{Circuit, Operation, Register};
 use petgraph::stable_graph::{NodeIndex, StableDiGraph};
+use petgraph::Direction;
+use std::fmt::Write;
 
 /// Whether a wire carries quantum or classical data.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -63,6 +69,14 @@ pub struct DAGCircuit {
     pub num_cbits: usize,
     /// Custom gate definitions carried over from the source circuit.
     pub custom_gates: crate::ir::registry::GateRegistry,
+    /// Classical register layout, used to resolve which cbits a
+    /// conditional reads. When a condition's register is not listed here,
+    /// the conditional conservatively reads every cbit.
+    pub cregs: Vec<Register>,
+    /// Quantum registers and global phase of the source circuit, restored
+    /// by `Circuit::from(&dag)`.
+    pub qregs: Vec<Register>,
+    pub global_phase: f64,
 
     current_q_leaves: Vec<NodeIndex>,
     current_c_leaves: Vec<NodeIndex>,
@@ -106,6 +120,9 @@ impl DAGCircuit {
             num_qubits,
             num_cbits,
             custom_gates: crate::ir::registry::GateRegistry::new(),
+            cregs: Vec::new(),
+            qregs: Vec::new(),
+            global_phase: 0.0,
             current_q_leaves,
             current_c_leaves,
         }
@@ -119,12 +136,20 @@ impl DAGCircuit {
             Operation::Measure { qubit, cbit } => (vec![*qubit], vec![*cbit]),
             Operation::Reset { qubit } => (vec![*qubit], vec![]),
             Operation::Barrier { qubits } => (qubits.clone(), vec![]),
-            Operation::Conditional { op: inner, .. } => {
+            Operation::Conditional {
+                condition,
+                op: inner,
+            } => {
                 let q = inner.qubits().to_vec();
-                let c = match &**inner {
-                    Operation::Measure { cbit, .. } => vec![*cbit],
-                    _ => vec![],
+                let mut c: Vec<usize> = match self.cregs.iter().find(|r| r.name == condition.creg) {
+                    Some(reg) => (reg.start..reg.start + reg.size).collect(),
+                    None => (0..self.num_cbits).collect(),
                 };
+                if let Operation::Measure { cbit, .. } = &**inner {
+                    if !c.contains(cbit) {
+                        c.push(*cbit);
+                    }
+                }
                 (q, c)
             }
         };
@@ -195,6 +220,61 @@ impl DAGCircuit {
         }
     }
 
+    /// Builds the DAG of `circuit`; same as `DAGCircuit::from(circuit)`.
+    pub fn from_circuit(circuit: &Circuit) -> Self {
+        Self::from(circuit)
+    }
+
+    /// Linearizes the DAG back into a circuit; same as
+    /// `Circuit::from(&dag)`. Operations come out in a topological order
+    /// that prefers earlier-inserted nodes, so an unmodified DAG yields
+    /// its source circuit's operations in their original order.
+    pub fn to_circuit(&self) -> Circuit {
+        Circuit::from(self)
+    }
+
+    /// Renders the DAG in Graphviz DOT format.
+    ///
+    /// Operation nodes are labelled with their OpenQASM text; qubit edges
+    /// are solid and labelled `q<i>`, cbit edges are dashed and labelled
+    /// `c<i>`.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph circuit {\n");
+        for idx in self.graph.node_indices() {
+            let label = match &self.graph[idx] {
+                DAGNode::Op(op) => op.to_string().trim_end_matches(';').to_string(),
+                DAGNode::In(w) => format!("in {}", wire_label(w)),
+                DAGNode::Out(w) => format!("out {}", wire_label(w)),
+            };
+            let _ = writeln!(
+                out,
+                "  n{} [label=\"{}\"];",
+                idx.index(),
+                label.replace('"', "\\\"")
+            );
+        }
+        for edge in self.graph.edge_indices() {
+            let Some((src, dst)) = self.graph.edge_endpoints(edge) else {
+                continue;
+            };
+            let wire = &self.graph[edge];
+            let style = match wire.wire_type {
+                WireType::Qubit => "",
+                WireType::Cbit => ", style=dashed",
+            };
+            let _ = writeln!(
+                out,
+                "  n{} -> n{} [label=\"{}\"{}];",
+                src.index(),
+                dst.index(),
+                wire_label(wire),
+                style
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
     /// Removes a node, reconnecting each incoming edge directly to the
     /// outgoing edge on the same wire (so the DAG remains well-formed).
     pub fn remove_node(&mut self, node_idx: NodeIndex) {
@@ -228,10 +308,20 @@ impl DAGCircuit {
     }
 }
 
+fn wire_label(wire: &Wire) -> String {
+    match wire.wire_type {
+        WireType::Qubit => format!("q{}", wire.index),
+        WireType::Cbit => format!("c{}", wire.index),
+    }
+}
+
 impl From<&Circuit> for DAGCircuit {
     fn from(circuit: &Circuit) -> Self {
         let mut dag = DAGCircuit::new(circuit.num_qubits, circuit.num_cbits);
         dag.custom_gates = circuit.custom_gates.clone();
+        dag.cregs = circuit.cregs.clone();
+        dag.qregs = circuit.qregs.clone();
+        dag.global_phase = circuit.global_phase;
         for op in &circuit.operations {
             dag.add_op(op.clone());
         }
@@ -242,16 +332,107 @@ impl From<&Circuit> for DAGCircuit {
 
 impl From<&DAGCircuit> for Circuit {
     fn from(dag: &DAGCircuit) -> Self {
-        use petgraph::algo::toposort;
+        use std::cmp::Reverse;
+        use std::collections::{BinaryHeap, HashMap};
 
         let mut circuit = Circuit::new(dag.num_qubits, dag.num_cbits);
         circuit.custom_gates = dag.custom_gates.clone();
-        let sorted = toposort(&dag.graph, None).expect("DAG contains a cycle!");
-        for node_idx in sorted {
+        circuit.cregs = dag.cregs.clone();
+        circuit.qregs = dag.qregs.clone();
+        circuit.global_phase = dag.global_phase;
+
+        // Kahn's algorithm, always emitting the lowest ready node index.
+        let mut pending: HashMap<NodeIndex, usize> = dag
+            .graph
+            .node_indices()
+            .map(|n| (n, dag.graph.edges_directed(n, Direction::Incoming).count()))
+            .collect();
+        let mut ready: BinaryHeap<Reverse<NodeIndex>> = pending
+            .iter()
+            .filter(|&(_, &deg)| deg == 0)
+            .map(|(&n, _)| Reverse(n))
+            .collect();
+        while let Some(Reverse(node_idx)) = ready.pop() {
             if let DAGNode::Op(op) = &dag.graph[node_idx] {
                 circuit.add_op(op.clone());
             }
+            for next in dag.graph.neighbors_directed(node_idx, Direction::Outgoing) {
+                let deg = pending.get_mut(&next).expect("edge to a live node");
+                *deg -= 1;
+                if *deg == 0 {
+                    ready.push(Reverse(next));
+                }
+            }
         }
         circuit
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_qasm;
+
+    #[test]
+    fn test_conditional_depends_on_measure_via_cbit() {
+        let circuit = parse_qasm(
+            "OPENQASM 2.0;\nqreg q[2];\ncreg c[1];\nmeasure q[0] -> c[0];\nif (c==1) x q[1];\n",
+        )
+        .unwrap();
+        let dag = DAGCircuit::from(&circuit);
+
+        let find = |pred: fn(&Operation) -> bool| {
+            dag.graph
+                .node_indices()
+                .find(|&i| matches!(&dag.graph[i], DAGNode::Op(op) if pred(op)))
+                .unwrap()
+        };
+        let measure = find(|op| matches!(op, Operation::Measure { .. }));
+        let cond = find(|op| matches!(op, Operation::Conditional { .. }));
+
+        let edge = dag.graph.find_edge(measure, cond).expect("classical edge");
+        assert_eq!(
+            dag.graph[edge],
+            Wire {
+                wire_type: WireType::Cbit,
+                index: 0,
+            }
+        );
+
+        let dot = dag.to_dot();
+        assert!(
+            dot.contains(&format!(
+                "n{} -> n{} [label=\"c0\", style=dashed];",
+                measure.index(),
+                cond.index()
+            )),
+            "{dot}"
+        );
+        assert_eq!(Circuit::from(&dag).operations, circuit.operations);
+    }
+
+    #[test]
+    fn test_round_trip_preserves_program_order() {
+        let circuit = parse_qasm(
+            "OPENQASM 2.0;\nqreg q[3];\ncreg c[3];\nh q[2];\nx q[0];\ncx q[0], q[1];\n\
+             t q[2];\nbarrier q[1], q[2];\nh q[1];\ny q[0];\nmeasure q -> c;\n\
+             if(c==3) z q[2];\n",
+        )
+        .unwrap();
+        let dag = DAGCircuit::from_circuit(&circuit);
+        let back = dag.to_circuit();
+        assert_eq!(back.operations, circuit.operations);
+        assert_eq!(back.cregs, circuit.cregs);
+
+        // The barrier orders `t q[2]` before `h q[1]` across wires.
+        let node = |text: &str| {
+            dag.graph
+                .node_indices()
+                .find(|&i| matches!(&dag.graph[i], DAGNode::Op(op) if op.to_string() == text))
+                .unwrap()
+        };
+        let barrier = node("barrier q[1], q[2];");
+        assert!(dag.graph.find_edge(node("t q[2];"), barrier).is_some());
+        assert!(dag.graph.find_edge(barrier, node("h q[1];")).is_some());
+    }
+}
@@ -1,7 +1,7 @@
 //! Basis decomposition pass with caching of custom gate expansions.
 
 use crate::error::{QRustError, Result};
-use crate::ir::ast::ParsedStatement;
+use crate::ir::ast::{Expr, ParsedStatement};
 use crate::ir::registry::GateRegistry;
 use crate::ir::{Circuit, GateDefinition, GateType, Operation};
 use std::collections::HashMap;
@@ -23,8 +23,7 @@ struct CachedTemplate {
 }
 
 pub fn try_decompose_basis(circuit: &Circuit) -> Result<Circuit> {
-    let mut result = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-    result.custom_gates = circuit.custom_gates.clone();
+    let mut result = circuit.empty_like();
     let mut cache: HashMap<String, CachedTemplate> = HashMap::new();
 
     for op in &circuit.operations {
@@ -47,39 +46,75 @@ pub fn decompose_basis(circuit: &Circuit) -> Circuit {
     })
 }
 
+/// Recursively inlines every custom gate in `circuit`, including those
+/// under a classical condition, using the circuit's own definitions. See
+/// [`try_unroll_custom_gates_with`].
 pub fn try_unroll_custom_gates(circuit: &Circuit) -> Result<Circuit> {
-    let mut result = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-    result.custom_gates = circuit.custom_gates.clone();
+    try_unroll_custom_gates_with(circuit, &circuit.custom_gates)
+}
+
+/// Recursively inlines every custom gate in `circuit` — including those
+/// under a classical condition — using the definitions in `registry`
+/// instead of the circuit's own.
+///
+/// Standard gates are left as-is. The result carries `registry` as its
+/// custom-gate table.
+///
+/// # Errors
+/// Returns [`QRustError::Decomposition`] naming the first custom gate with
+/// no definition in `registry`.
+pub fn try_unroll_custom_gates_with(circuit: &Circuit, registry: &GateRegistry) -> Result<Circuit> {
+    let mut result = circuit.empty_like();
+    result.custom_gates = registry.clone();
     let mut cache: HashMap<String, CachedTemplate> = HashMap::new();
 
     for op in &circuit.operations {
-        match op {
-            Operation::Gate {
-                name,
-                qubits,
-                params,
-            } if matches!(name, GateType::Custom(_)) => {
-                expand_gate_custom_only(
-                    &mut result,
-                    &circuit.custom_gates,
-                    name,
-                    qubits,
-                    params,
-                    &mut cache,
-                )?;
+        unroll_op_custom_only(&mut result, registry, op, &mut cache)?;
+    }
+    Ok(result)
+}
+
+fn unroll_op_custom_only(
+    circuit: &mut Circuit,
+    registry: &GateRegistry,
+    op: &Operation,
+    cache: &mut HashMap<String, CachedTemplate>,
+) -> Result<()> {
+    match op {
+        Operation::Gate {
+            name,
+            qubits,
+            params,
+        } if matches!(name, GateType::Custom(_)) => {
+            expand_gate_custom_only(circuit, registry, name, qubits, params, cache)
+        }
+        Operation::Conditional {
+            condition,
+            op: inner,
+        } => {
+            let mut tmp = Circuit::new(circuit.num_qubits, circuit.num_cbits);
+            unroll_op_custom_only(&mut tmp, registry, inner, cache)?;
+            for sub in tmp.operations {
+                circuit.add_op(Operation::Conditional {
+                    condition: condition.clone(),
+                    op: Box::new(sub),
+                });
             }
-            other => result.add_op(other.clone()),
+            Ok(())
+        }
+        other => {
+            circuit.add_op(other.clone());
+            Ok(())
         }
     }
-    Ok(result)
 }
 
 /// Infallible wrapper around [`try_unroll_custom_gates`].
 ///
 /// On error, emits a diagnostic via `Q_RUST_LOG` and returns the original
-/// circuit unchanged. Downstream custom-gate ops that were not unrolled will
-/// be treated as 1-qubit identities by the simulator, so callers that can
-/// propagate errors should prefer [`try_unroll_custom_gates`] directly.
+/// circuit unchanged. The simulator rejects custom gates that were not
+/// unrolled, so callers that can propagate errors should prefer
+/// [`try_unroll_custom_gates`] directly.
 pub fn unroll_custom_gates(circuit: &Circuit) -> Circuit {
     try_unroll_custom_gates(circuit).unwrap_or_else(|e| {
         crate::transpiler::warn_diagnostic(format_args!(
@@ -161,7 +196,12 @@ fn get_or_build_template(
             let gt = inner_name_str
                 .parse::<GateType>()
                 .unwrap_or_else(|_| GateType::Custom(inner_name_str.clone()));
-            steps.push((gt, qidx, inner_params.clone()));
+            // `u2(φ, λ)` is `U(π/2, φ, λ)`, as in the top-level parser.
+            let mut params = inner_params.clone();
+            if inner_name_str == "u2" && params.len() == 2 {
+                params.insert(0, Expr::Float(std::f64::consts::FRAC_PI_2));
+            }
+            steps.push((gt, qidx, params));
         }
     }
     let t = CachedTemplate { steps };
@@ -180,6 +220,15 @@ fn expand_gate_custom_only(
     let GateType::Custom(ref custom_name) = name else {
         return Ok(());
     };
+    if registry.get(custom_name).is_none() && registry.unitary(custom_name).is_some() {
+        // Matrix-defined gates are applied directly by the simulator.
+        circuit.add_op(Operation::Gate {
+            name: name.clone(),
+            qubits: qubits.to_vec(),
+            params: params.to_vec(),
+        });
+        return Ok(());
+    }
     let def = registry.get(custom_name).ok_or_else(|| {
         QRustError::Decomposition(format!("custom gate '{}' not in registry", custom_name))
     })?;
@@ -299,8 +348,7 @@ fn expand_gate(
 /// coupling map — but the reverse edge exists — by sandwiching with H on
 /// both wires: `CX(a,b) = (H⊗H) · CX(b,a) · (H⊗H)`.
 pub fn reorient_cx_for_coupling(circuit: &Circuit, backend: &crate::backend::Backend) -> Circuit {
-    let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-    out.custom_gates = circuit.custom_gates.clone();
+    let mut out = circuit.empty_like();
 
     for op in &circuit.operations {
         match op {
@@ -429,6 +477,38 @@ mod tests {
         assert_eq!(d.operations.len(), 3);
     }
 
+    #[test]
+    fn test_nested_parameter_expressions_resolve_per_scope() {
+        let c = crate::parser::parse_qasm(
+            r#"
+            OPENQASM 2.0;
+            gate inner(s) q { rz(s) q; }
+            gate middle(t) q { inner(t/2) q; }
+            gate outer(u) q { middle(2*u + pi) q; }
+            qreg q[1];
+            outer(0.5) q[0];
+            middle(pi) q[0];
+            "#,
+        )
+        .unwrap();
+        let flat = try_unroll_custom_gates(&c).unwrap();
+        let angles: Vec<f64> = flat
+            .operations
+            .iter()
+            .map(|op| match op {
+                Operation::Gate {
+                    name: GateType::RZ,
+                    params,
+                    ..
+                } => params[0],
+                other => panic!("expected rz, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(angles.len(), 2);
+        assert!((angles[0] - (2.0 * 0.5 + PI) / 2.0).abs() < 1e-12);
+        assert!((angles[1] - PI / 2.0).abs() < 1e-12);
+    }
+
     #[test]
     fn test_missing_custom_gate_returns_err() {
         let mut c = Circuit::new(1, 0);
@@ -6,8 +6,9 @@
 //! topology, this often converges to a zero-SWAP layout.
 
 use crate::backend::Backend;
+use crate::error::{QRustError, Result};
 #[allow(unused_imports)]
-use crate::ir::{Circuit, GateType, Operation};
+use crate::ir::{Circuit, GateType, Operation, Register};
 use crate::transpiler::pass::Pass;
 use crate::transpiler::property_set::PropertySet;
 use crate::transpiler::routing::Layout;
@@ -319,6 +320,63 @@ impl Pass for SabreLayoutPass {
     }
 }
 
+/// Pre-flight check that a circuit fits on a backend.
+///
+/// Fails if the circuit needs more qubits than the backend offers;
+/// otherwise widens the circuit to the backend size. When the circuit has
+/// register metadata, the added qubits are declared as an `ancilla`
+/// register so named registers still tile the qubit index space.
+#[derive(Debug, Clone)]
+pub struct FitToBackendPass {
+    pub backend: Backend,
+}
+
+impl FitToBackendPass {
+    /// Fallible entry point.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::InsufficientQubits`] if
+    /// `circuit.num_qubits > backend.num_qubits`.
+    pub fn try_run(&self, circuit: &Circuit) -> Result<Circuit> {
+        let width = self.backend.num_qubits;
+        if circuit.num_qubits > width {
+            return Err(QRustError::InsufficientQubits {
+                circuit: circuit.num_qubits,
+                backend: width,
+            });
+        }
+        let mut out = circuit.clone();
+        if width > circuit.num_qubits && !out.qregs.is_empty() {
+            out.qregs.push(Register::new(
+                "ancilla",
+                circuit.num_qubits,
+                width - circuit.num_qubits,
+            ));
+        }
+        out.num_qubits = width;
+        Ok(out)
+    }
+}
+
+impl Pass for FitToBackendPass {
+    fn name(&self) -> &str {
+        "FitToBackendPass"
+    }
+
+    fn run(&self, circuit: &Circuit, property_set: &mut PropertySet) -> Circuit {
+        match self.try_run(circuit) {
+            Ok(c) => c,
+            Err(e) => {
+                property_set.insert("fit_to_backend_error", e.to_string());
+                crate::transpiler::warn_diagnostic(format_args!(
+                    "FitToBackendPass failed: {e}; returning original circuit"
+                ));
+                circuit.clone()
+            }
+        }
+    }
+}
+
 /// Fisher-Yates shuffle followed by truncation produces an injective mapping
 /// into `[0, num_physical)`. The `expect` is appropriate here — a panic
 /// indicates a Fisher-Yates correctness bug, not user error.
@@ -356,6 +414,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fit_to_backend() {
+        let pass = FitToBackendPass {
+            backend: Backend::linear(5),
+        };
+        let err = pass.try_run(&Circuit::new(6, 0)).unwrap_err();
+        assert!(matches!(
+            err,
+            QRustError::InsufficientQubits {
+                circuit: 6,
+                backend: 5
+            }
+        ));
+
+        let c = crate::parser::parse_qasm("OPENQASM 2.0;\nqreg q[3];\nh q[0];\n").unwrap();
+        let fitted = pass.try_run(&c).unwrap();
+        assert_eq!(fitted.num_qubits, 5);
+        assert_eq!(fitted.operations, c.operations);
+        assert_eq!(fitted.qregs.last(), Some(&Register::new("ancilla", 3, 2)));
+    }
+
     #[test]
     fn test_layout_pass_improves_chain() {
         let mut c = Circuit::new(5, 0);
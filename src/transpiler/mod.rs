@@ -23,6 +23,55 @@ use crate::ir::{Circuit, GateType, Operation};
 use pass::{Pass, PassManager};
 use std::collections::HashSet;
 
+/// Named optimization levels, mirroring [`TranspilerConfig::optimization_level`].
+///
+/// | Level    | `u8` | Optimization passes                                          |
+/// |----------|------|--------------------------------------------------------------|
+/// | `None`   | 0    | none — only routing (with a backend) and basis translation   |
+/// | `Light`  | 1    | gate crystallization, parameter simplification               |
+/// | `Medium` | 2    | + rotation merge, cancellations, SABRE layout, wider routing |
+/// | `Heavy`  | 3    | + widest routing beam and post-routing fusion                |
+///
+/// Use [`PassManager::from_opt_level`] to inspect or extend the exact pass
+/// list for a level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum OptLevel {
+    None,
+    #[default]
+    Light,
+    Medium,
+    Heavy,
+}
+
+impl OptLevel {
+    /// The numeric level used by [`TranspilerConfig::optimization_level`].
+    pub fn as_u8(self) -> u8 {
+        match self {
+            OptLevel::None => 0,
+            OptLevel::Light => 1,
+            OptLevel::Medium => 2,
+            OptLevel::Heavy => 3,
+        }
+    }
+
+    /// Maps a numeric level to an [`OptLevel`], clamping values above 3.
+    pub fn from_u8(level: u8) -> Self {
+        match level {
+            0 => OptLevel::None,
+            1 => OptLevel::Light,
+            2 => OptLevel::Medium,
+            _ => OptLevel::Heavy,
+        }
+    }
+}
+
+impl From<OptLevel> for u8 {
+    fn from(level: OptLevel) -> Self {
+        level.as_u8()
+    }
+}
+
 /// Top-level transpiler configuration.
 ///
 /// Construct via [`TranspilerConfig::builder`]. All fields are individually
@@ -69,6 +118,11 @@ impl TranspilerConfig {
     pub fn builder() -> TranspilerConfigBuilder {
         TranspilerConfigBuilder::default()
     }
+
+    /// The configured optimization level as an [`OptLevel`].
+    pub fn opt_level(&self) -> OptLevel {
+        OptLevel::from_u8(self.optimization_level)
+    }
 }
 
 /// Builder for [`TranspilerConfig`]. Method chaining is the intended idiom.
@@ -92,6 +146,11 @@ impl TranspilerConfigBuilder {
         self.optimization_level = Some(level);
         self
     }
+    /// Set the optimization level by name; equivalent to
+    /// [`Self::optimization_level`] with [`OptLevel::as_u8`].
+    pub fn opt_level(self, level: OptLevel) -> Self {
+        self.optimization_level(level.as_u8())
+    }
     /// Attach a hardware backend (enables layout + routing).
     pub fn backend(mut self, backend: crate::backend::Backend) -> Self {
         self.backend = Some(backend);
@@ -156,8 +215,7 @@ impl Pass for KakSynthesisPass {
         use crate::transpiler::synthesis::kak::KakSynthesizer;
         use crate::transpiler::synthesis::Synthesizer;
 
-        let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-        out.custom_gates = circuit.custom_gates.clone();
+        let mut out = circuit.empty_like();
         let mut kak_invocations: usize = 0;
         let mut analytic_contract_violations: usize = 0;
 
@@ -262,8 +320,7 @@ impl Pass for NativeBasisTranslationPass {
             return circuit.clone();
         }
 
-        let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-        out.custom_gates = circuit.custom_gates.clone();
+        let mut out = circuit.empty_like();
         let pi = std::f64::consts::PI;
 
         for op in &circuit.operations {
@@ -330,7 +387,11 @@ enum Stage {
 /// optimization a second time.
 fn build_pass_manager_for(config: &TranspilerConfig, stage: Stage) -> Result<PassManager> {
     let mut pm = PassManager::new();
+    add_stage_passes(&mut pm, config, stage)?;
+    Ok(pm)
+}
 
+fn add_stage_passes(pm: &mut PassManager, config: &TranspilerConfig, stage: Stage) -> Result<()> {
     match stage {
         Stage::Optimize => {
             if config.optimization_level >= 1 {
@@ -414,7 +475,34 @@ fn build_pass_manager_for(config: &TranspilerConfig, stage: Stage) -> Result<Pas
         }
     }
 
-    Ok(pm)
+    Ok(())
+}
+
+impl PassManager {
+    /// Builds the full default pipeline (optimization, then layout/routing
+    /// and lowering) that [`transpile`] runs for `level`, with basis
+    /// decomposition enabled.
+    ///
+    /// Routing passes are only added when `backend` is given; its
+    /// `basis_gates`, if any, become the target basis.
+    ///
+    /// # Errors
+    /// Returns [`crate::QRustError::NonUniversalBasisGateSet`] if the
+    /// backend's basis is not universal.
+    pub fn from_opt_level(
+        level: OptLevel,
+        backend: Option<crate::backend::Backend>,
+    ) -> Result<PassManager> {
+        let mut builder = TranspilerConfig::builder().opt_level(level);
+        if let Some(backend) = backend {
+            builder = builder.backend(backend);
+        }
+        let config = builder.build();
+        let mut pm = PassManager::new();
+        add_stage_passes(&mut pm, &config, Stage::Optimize)?;
+        add_stage_passes(&mut pm, &config, Stage::LayoutAndLower)?;
+        Ok(pm)
+    }
 }
 
 pub fn transpile(circuit: &Circuit, config: Option<TranspilerConfig>) -> Result<Circuit> {
@@ -425,6 +513,43 @@ pub fn transpile(circuit: &Circuit, config: Option<TranspilerConfig>) -> Result<
     Ok(lower_pm.run(&after_opt))
 }
 
+/// Upper bound on cleanup rounds in [`optimize_to_fixed_point`]; each round
+/// normally shrinks the circuit, so this is only a guard against passes
+/// that keep rewriting without converging.
+const MAX_OPTIMIZE_ROUNDS: usize = 16;
+
+/// Backend-agnostic cleanup behind [`Circuit::optimize`]: drops identity
+/// rotations, cancels adjacent inverses, merges rotations and fuses
+/// single-qubit `U` runs, repeating until a round leaves the circuit
+/// unchanged. Registers and global phase are carried over from the input
+/// (plus the sign flips recorded by
+/// [`optimization::ParameterSimplificationPass`]); regions are not, since
+/// operation indices shift.
+pub(crate) fn optimize_to_fixed_point(circuit: &Circuit) -> Circuit {
+    let mut pm = PassManager::new();
+    pm.add_pass(Box::new(
+        optimization::ParameterSimplificationPass::default(),
+    ));
+    pm.add_pass(Box::new(optimization::InverseCancellationPass));
+    pm.add_pass(Box::new(optimization::RotationMergePass));
+    pm.add_pass(Box::new(optimization::CommutationCancellationPass));
+    pm.add_pass(Box::new(optimization::GateFusionPass));
+    pm.add_pass(Box::new(
+        optimization::ParameterSimplificationPass::default(),
+    ));
+
+    let mut current = circuit.clone();
+    for _ in 0..MAX_OPTIMIZE_ROUNDS {
+        let next = pm.run(&current);
+        let converged = next.content_hash() == current.content_hash();
+        current = next;
+        if converged {
+            break;
+        }
+    }
+    current
+}
+
 /// [E2E-NEW-FEATURE] Like [`transpile`], but additionally returns a
 /// [`TranspilationReport`] describing per-stage circuit metrics.
 ///
@@ -475,6 +600,32 @@ mod tests {
         assert_eq!(t.operations.len(), 1);
     }
 
+    #[test]
+    fn test_transpile_keeps_registers_for_conditions() {
+        use crate::simulator::sample;
+        use std::collections::HashMap;
+
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[2];\ncreg a[1];\ncreg b[1];\n\
+             // @gphase 0.25\nx q[0];\nmeasure q[0] -> a[0];\nif(b==0) x q[1];\n\
+             measure q[1] -> b[0];\n",
+        )
+        .unwrap();
+        let expected = HashMap::from([("11".to_string(), 10)]);
+        assert_eq!(sample(&c, 10, 7).unwrap(), expected);
+
+        let simplified = optimization::ParameterSimplificationPass::default()
+            .run(&c, &mut property_set::PropertySet::new());
+        let transpiled = transpile(&c, None).unwrap();
+        for out in [simplified, transpiled] {
+            assert_eq!(out.cregs, c.cregs);
+            assert_eq!(out.global_phase, 0.25);
+            assert_eq!(sample(&out, 10, 7).unwrap(), expected);
+            let reparsed = crate::parser::parse_qasm(&out.to_qasm(None)).unwrap();
+            assert_eq!(sample(&reparsed, 10, 7).unwrap(), expected);
+        }
+    }
+
     #[test]
     fn test_builder_clamps_level() {
         let cfg = TranspilerConfig::builder().optimization_level(255).build();
@@ -535,6 +686,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_opt_level_round_trips_through_builder() {
+        for level in [
+            OptLevel::None,
+            OptLevel::Light,
+            OptLevel::Medium,
+            OptLevel::Heavy,
+        ] {
+            let cfg = TranspilerConfig::builder().opt_level(level).build();
+            assert_eq!(cfg.opt_level(), level);
+            assert_eq!(cfg.optimization_level, u8::from(level));
+        }
+        assert_eq!(OptLevel::from_u8(7), OptLevel::Heavy);
+    }
+
+    #[test]
+    fn test_opt_level_none_runs_only_routing_and_translation() {
+        let pm = PassManager::from_opt_level(OptLevel::None, Some(Backend::linear(3))).unwrap();
+        assert_eq!(
+            pm.pass_names(),
+            vec![
+                "BeamSabrePass",
+                "CxDirectionPass",
+                "BasisDecompositionPass",
+                "KakSynthesisPass"
+            ]
+        );
+
+        let medium = PassManager::from_opt_level(OptLevel::Medium, None).unwrap();
+        let names = medium.pass_names();
+        assert!(names.contains(&"InverseCancellationPass"));
+        assert!(!names.contains(&"BeamSabrePass"));
+    }
+
     #[test]
     fn test_builder_threads_lookahead_strategy() {
         let cfg = TranspilerConfig::builder()
@@ -95,10 +95,8 @@ fn fuse_segment(circuit: &Circuit) -> Circuit {
 /// Splits `circuit` into segments separated by `Barrier` ops, runs `f` on each,
 /// and re-assembles. Barriers are preserved.
 fn run_in_segments<F: Fn(&Circuit) -> Circuit>(circuit: &Circuit, f: F) -> Circuit {
-    let mut result = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-    result.custom_gates = circuit.custom_gates.clone();
-    let mut current = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-    current.custom_gates = circuit.custom_gates.clone();
+    let mut result = circuit.empty_like();
+    let mut current = circuit.empty_like();
 
     for op in &circuit.operations {
         if is_barrier(op) {
@@ -107,8 +105,7 @@ fn run_in_segments<F: Fn(&Circuit) -> Circuit>(circuit: &Circuit, f: F) -> Circu
                 result.add_op(p);
             }
             result.add_op(op.clone());
-            current = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-            current.custom_gates = circuit.custom_gates.clone();
+            current = circuit.empty_like();
         } else {
             current.add_op(op.clone());
         }
@@ -358,8 +355,7 @@ fn swap_simplify_segment(circuit: &Circuit) -> Circuit {
         }
     }
 
-    let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-    out.custom_gates = circuit.custom_gates.clone();
+    let mut out = circuit.empty_like();
     for (i, op) in circuit.operations.iter().enumerate() {
         if alive[i] {
             out.add_op(op.clone());
@@ -377,6 +373,16 @@ fn involves_any(op: &Operation, qubits: &[usize]) -> bool {
     }
 }
 
+/// Wraps rotation angles into `[0, 2π)` and drops rotations that are the
+/// identity: `RX/RY/RZ(θ)` with `θ ≡ 0`, and `U(θ,φ,λ)` with `θ ≡ 0` and
+/// `φ + λ ≡ 0` (mod 2π, within `epsilon`).
+///
+/// In the QASM 2.0 convention `U(0,φ,λ) = diag(1, e^{i(φ+λ)})` carries no
+/// global phase, so `U(0,0,0)` is exactly the identity. An `RX`/`RY` angle
+/// or `U`'s `θ` shifted by an odd number of turns flips the gate's sign:
+/// `U(2π,0,0)` and `RX(2π)` are `-I`. Such gates are still wrapped or
+/// dropped, and π is added to the output's `global_phase` for each sign
+/// flip, so the result is exactly equal to the input.
 #[derive(Debug, Clone, Copy)]
 pub struct ParameterSimplificationPass {
     pub epsilon: f64,
@@ -398,9 +404,11 @@ impl Pass for ParameterSimplificationPass {
         circuit: &Circuit,
         _property_set: &mut crate::transpiler::property_set::PropertySet,
     ) -> Circuit {
-        let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-        out.custom_gates = circuit.custom_gates.clone();
+        let mut out = circuit.empty_like();
         let two_pi = 2.0 * std::f64::consts::PI;
+        // Parity of the sign flips introduced by wrapping half-angle
+        // parameters.
+        let mut negated = false;
 
         for op in &circuit.operations {
             match op {
@@ -415,32 +423,33 @@ impl Pass for ParameterSimplificationPass {
                         name,
                         GateType::RX | GateType::RY | GateType::RZ | GateType::U
                     ) {
-                        for p in &mut np {
-                            *p %= two_pi;
-                            if *p < 0.0 {
-                                *p += two_pi;
+                        // RX/RY angles and U's θ enter as half angles; RZ
+                        // (`U(0,0,φ)`) and U's φ and λ are 2π-periodic.
+                        let half_angle = !matches!(name, GateType::RZ);
+                        if let Some(&theta) = np.first().filter(|_| half_angle) {
+                            let mut turns = theta.div_euclid(two_pi);
+                            if two_pi - theta.rem_euclid(two_pi) < self.epsilon {
+                                turns += 1.0;
                             }
+                            negated ^= turns.rem_euclid(2.0) == 1.0;
+                        }
+                        for p in &mut np {
+                            *p = p.rem_euclid(two_pi);
                         }
                         match name {
                             GateType::RX | GateType::RY | GateType::RZ => {
-                                if let Some(theta) = np.first() {
-                                    if theta.abs() < self.epsilon
-                                        || (theta - two_pi).abs() < self.epsilon
-                                    {
+                                if let Some(&theta) = np.first() {
+                                    if near_zero_mod_2pi(theta, self.epsilon) {
                                         keep = false;
                                     }
                                 }
                             }
-                            GateType::U if np.len() >= 3 => {
-                                let theta = np[0];
-                                if theta.abs() < self.epsilon {
-                                    let phase_sum = (np[1] + np[2]) % two_pi;
-                                    if phase_sum.abs() < self.epsilon
-                                        || (phase_sum - two_pi).abs() < self.epsilon
-                                    {
-                                        keep = false;
-                                    }
-                                }
+                            GateType::U
+                                if np.len() >= 3
+                                    && near_zero_mod_2pi(np[0], self.epsilon)
+                                    && near_zero_mod_2pi(np[1] + np[2], self.epsilon) =>
+                            {
+                                keep = false;
                             }
                             _ => {}
                         }
@@ -456,10 +465,20 @@ impl Pass for ParameterSimplificationPass {
                 other => out.add_op(other.clone()),
             }
         }
+        if negated {
+            out.global_phase += std::f64::consts::PI;
+        }
         out
     }
 }
 
+/// True if `angle` is within `eps` of a multiple of 2π.
+fn near_zero_mod_2pi(angle: f64, eps: f64) -> bool {
+    let two_pi = 2.0 * std::f64::consts::PI;
+    let r = angle.rem_euclid(two_pi);
+    r < eps || two_pi - r < eps
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct GateCrystallizationPass {
     pub epsilon: f64,
@@ -481,8 +500,7 @@ impl Pass for GateCrystallizationPass {
         circuit: &Circuit,
         _property_set: &mut crate::transpiler::property_set::PropertySet,
     ) -> Circuit {
-        let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-        out.custom_gates = circuit.custom_gates.clone();
+        let mut out = circuit.empty_like();
         let pi = std::f64::consts::PI;
 
         for op in &circuit.operations {
@@ -583,6 +601,12 @@ impl Pass for GateCrystallizationPass {
     }
 }
 
+/// Merges consecutive same-axis rotations (`RX`/`RY`/`RZ`, and `CRX`/`CRY`/
+/// `CRZ` on the same qubit pair) by adding their angles.
+///
+/// "Consecutive" is per wire: the pass works on the DAG, so gates on
+/// other qubits in between (`rz(a) q[0]; h q[1]; rz(b) q[0];`) do not
+/// block a merge, while any operation on the same qubit does.
 #[derive(Debug, Clone, Copy)]
 pub struct RotationMergePass;
 
@@ -691,8 +715,7 @@ impl Pass for CrossConjugationPass {
 }
 
 fn cross_conjugation_segment(circuit: &Circuit) -> Circuit {
-    let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-    out.custom_gates = circuit.custom_gates.clone();
+    let mut out = circuit.empty_like();
     let mut skip = std::collections::HashSet::new();
 
     for i in 0..circuit.operations.len() {
@@ -890,6 +913,165 @@ fn are_inverses(op1: &Operation, op2: &Operation) -> bool {
     }
 }
 
+/// Removes resets that are the last operation on their qubit.
+///
+/// Nothing can observe the state of a qubit after its final operation, so a
+/// trailing reset is a no-op. A reset followed by any later use (gate,
+/// measurement, conditional) is kept — e.g. reset-then-measure always reads
+/// 0 and is meaningful. Barriers do not count as a use.
+#[derive(Debug, Clone, Copy)]
+pub struct TerminalResetRemovalPass;
+
+impl Pass for TerminalResetRemovalPass {
+    fn name(&self) -> &str {
+        "TerminalResetRemovalPass"
+    }
+
+    fn run(
+        &self,
+        circuit: &Circuit,
+        _property_set: &mut crate::transpiler::property_set::PropertySet,
+    ) -> Circuit {
+        let mut used = vec![false; circuit.num_qubits];
+        let mut keep = vec![true; circuit.operations.len()];
+        for (i, op) in circuit.operations.iter().enumerate().rev() {
+            match op {
+                Operation::Reset { qubit } => {
+                    if !used.get(*qubit).copied().unwrap_or(true) {
+                        keep[i] = false;
+                    }
+                }
+                Operation::Barrier { .. } => {}
+                other => {
+                    for &q in other.qubits() {
+                        if let Some(slot) = used.get_mut(q) {
+                            *slot = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut out = circuit.empty_like();
+        for (op, keep) in circuit.operations.iter().zip(keep) {
+            if keep {
+                out.add_op(op.clone());
+            }
+        }
+        out
+    }
+}
+
+/// Drops the idle qubits (see [`Circuit::idle_qubits`]) and renumbers the
+/// rest contiguously, keeping their relative order. Barriers lose their idle
+/// qubits and disappear if none remain; quantum registers shrink
+/// accordingly. Classical bits are untouched.
+#[derive(Debug, Clone, Copy)]
+pub struct RemoveIdleQubitsPass;
+
+impl Pass for RemoveIdleQubitsPass {
+    fn name(&self) -> &str {
+        "RemoveIdleQubitsPass"
+    }
+
+    fn run(
+        &self,
+        circuit: &Circuit,
+        _property_set: &mut crate::transpiler::property_set::PropertySet,
+    ) -> Circuit {
+        let used = circuit.qubits_used();
+        let mut map = vec![None; circuit.num_qubits];
+        let mut next = 0;
+        for (q, slot) in map.iter_mut().enumerate() {
+            if used.contains(&q) {
+                *slot = Some(next);
+                next += 1;
+            }
+        }
+        if next == circuit.num_qubits {
+            return circuit.clone();
+        }
+
+        let mut out = circuit.clone();
+        out.num_qubits = next;
+        out.operations.clear();
+        let mut dropped_op = false;
+        for op in &circuit.operations {
+            let mut op = op.clone();
+            if let Operation::Barrier { qubits } = &mut op {
+                qubits.retain(|&q| map.get(q).is_some_and(Option::is_some));
+                if qubits.is_empty() {
+                    dropped_op = true;
+                    continue;
+                }
+            }
+            op.map_qubits(&mut |q| map.get(q).copied().flatten().unwrap_or(q));
+            out.add_op(op);
+        }
+        if dropped_op {
+            out.regions.clear();
+        }
+        for reg in &mut out.qregs {
+            let kept = |r: std::ops::Range<usize>| r.filter(|q| map[*q].is_some()).count();
+            let end = (reg.start + reg.size).min(map.len());
+            reg.size = kept(reg.start.min(end)..end);
+            reg.start = kept(0..reg.start.min(end));
+        }
+        out.qregs.retain(|r| r.size > 0);
+        out
+    }
+}
+
+/// Moves each measurement as late as it can legally go, so measurements
+/// end up grouped (e.g. for backends that prefer terminal readout).
+///
+/// A measurement commutes past any operation that neither touches its
+/// qubit nor writes its cbit. Conditionals read classical bits, so no
+/// measurement moves past one; barriers on the measured qubit also stop
+/// it. Unlike full measurement deferral, no gates are rewritten.
+#[derive(Debug, Clone, Copy)]
+pub struct LateMeasurementPass;
+
+impl Pass for LateMeasurementPass {
+    fn name(&self) -> &str {
+        "LateMeasurementPass"
+    }
+
+    fn run(
+        &self,
+        circuit: &Circuit,
+        _property_set: &mut crate::transpiler::property_set::PropertySet,
+    ) -> Circuit {
+        fn blocks(op: &Operation, qubit: usize, cbit: usize) -> bool {
+            match op {
+                Operation::Measure { qubit: q, cbit: c } => *q == qubit || *c == cbit,
+                Operation::Conditional { .. } => true,
+                other => other.qubits().contains(&qubit),
+            }
+        }
+
+        let mut out = circuit.empty_like();
+        // Measurements held back so far, in original order.
+        let mut pending: Vec<(usize, usize)> = Vec::new();
+        for op in &circuit.operations {
+            let (flush, keep): (Vec<_>, Vec<_>) =
+                pending.iter().partition(|&&(q, c)| blocks(op, q, c));
+            for (qubit, cbit) in flush {
+                out.add_op(Operation::Measure { qubit, cbit });
+            }
+            pending = keep;
+            match op {
+                Operation::Measure { qubit, cbit } => pending.push((*qubit, *cbit)),
+                other => out.add_op(other.clone()),
+            }
+        }
+        for (qubit, cbit) in pending {
+            out.add_op(Operation::Measure { qubit, cbit });
+        }
+        out
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -991,6 +1173,171 @@ mod tests {
         assert_eq!(r.operations.len(), 3);
     }
 
+    #[test]
+    fn test_terminal_reset_removed() {
+        let mut c = Circuit::new(2, 1);
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Reset { qubit: 0 });
+        c.add_op(Operation::Barrier { qubits: vec![0, 1] });
+        c.cregs = vec![crate::ir::Register::new("m", 0, 1)];
+        c.global_phase = 0.5;
+        let r = TerminalResetRemovalPass.run(&c, &mut new_props());
+        assert_eq!(r.operations.len(), 3);
+        assert_eq!((r.cregs, r.global_phase), (c.cregs, 0.5));
+        assert!(!r
+            .operations
+            .iter()
+            .any(|op| matches!(op, Operation::Reset { .. })));
+    }
+
+    #[test]
+    fn test_reset_then_measure_kept() {
+        let mut c = Circuit::new(1, 1);
+        c.add_op(Operation::Reset { qubit: 0 });
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        let r = TerminalResetRemovalPass.run(&c, &mut new_props());
+        assert_eq!(r.operations, c.operations);
+    }
+
+    #[test]
+    fn test_remove_idle_qubits_compacts() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[5];\ncreg c[5];\nh q[1];\n\
+             cx q[4], q[3];\nbarrier q;\nmeasure q[3] -> c[3];\n",
+        )
+        .unwrap();
+        let r = RemoveIdleQubitsPass.run(&c, &mut new_props());
+        assert_eq!((r.num_qubits, r.num_cbits), (3, 5));
+        assert_eq!(
+            r.operations,
+            vec![
+                Operation::Gate {
+                    name: GateType::H,
+                    qubits: vec![0],
+                    params: vec![],
+                },
+                Operation::Gate {
+                    name: GateType::CX,
+                    qubits: vec![2, 1],
+                    params: vec![],
+                },
+                Operation::Barrier {
+                    qubits: vec![0, 1, 2],
+                },
+                Operation::Measure { qubit: 1, cbit: 3 },
+            ]
+        );
+        assert!(r.to_qasm(None).contains("qreg q[3];"));
+
+        let untouched = RemoveIdleQubitsPass.run(&r, &mut new_props());
+        assert_eq!(untouched, r);
+    }
+
+    #[test]
+    fn test_late_measurement_groups_measures() {
+        let mut c = Circuit::new(2, 2);
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Gate {
+            name: GateType::X,
+            qubits: vec![1],
+            params: vec![],
+        });
+        c.add_op(Operation::Measure { qubit: 1, cbit: 1 });
+        let r = LateMeasurementPass.run(&c, &mut new_props());
+        assert_eq!(
+            r.operations,
+            vec![
+                Operation::Gate {
+                    name: GateType::X,
+                    qubits: vec![1],
+                    params: vec![],
+                },
+                Operation::Measure { qubit: 0, cbit: 0 },
+                Operation::Measure { qubit: 1, cbit: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_late_measurement_stops_at_own_qubit() {
+        let mut c = Circuit::new(1, 1);
+        c.add_op(Operation::Measure { qubit: 0, cbit: 0 });
+        c.add_op(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        });
+        let r = LateMeasurementPass.run(&c, &mut new_props());
+        assert_eq!(r.operations, c.operations);
+    }
+
+    #[test]
+    fn test_late_measurement_keeps_named_register_conditions() {
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[2];\ncreg a[1];\ncreg b[1];\n\
+             x q[0];\nmeasure q[0] -> a[0];\nif(b==0) x q[1];\nmeasure q[1] -> b[0];\n",
+        )
+        .unwrap();
+        let r = LateMeasurementPass.run(&c, &mut new_props());
+        assert_eq!(r.cregs, c.cregs);
+        assert_eq!(
+            crate::simulator::sample(&r, 10, 7).unwrap(),
+            std::collections::HashMap::from([("11".to_string(), 10)])
+        );
+    }
+
+    #[test]
+    fn test_rotation_merge_looks_past_disjoint_ops() {
+        let rz = |theta: f64| Operation::Gate {
+            name: GateType::RZ,
+            qubits: vec![0],
+            params: vec![theta],
+        };
+        let h = |q: usize| Operation::Gate {
+            name: GateType::H,
+            qubits: vec![q],
+            params: vec![],
+        };
+
+        let mut c = Circuit::new(3, 0);
+        c.add_op(rz(0.1));
+        c.add_op(h(1));
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![1, 2],
+            params: vec![],
+        });
+        c.add_op(rz(0.2));
+        let out = RotationMergePass.run(&c, &mut new_props());
+        let rzs: Vec<f64> = out
+            .operations
+            .iter()
+            .filter_map(|op| match op {
+                Operation::Gate {
+                    name: GateType::RZ,
+                    params,
+                    ..
+                } => Some(params[0]),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rzs.len(), 1, "{:?}", out.operations);
+        assert!((rzs[0] - 0.3).abs() < 1e-12);
+        assert_eq!(out.operations.len(), 3);
+
+        let mut blocked = Circuit::new(2, 0);
+        blocked.add_op(rz(0.1));
+        blocked.add_op(h(0));
+        blocked.add_op(rz(0.2));
+        let out = RotationMergePass.run(&blocked, &mut new_props());
+        assert_eq!(out.operations, blocked.operations);
+    }
+
     #[test]
     fn test_parameter_simplification_drops_zero_rx() {
         let mut c = Circuit::new(1, 0);
@@ -1002,4 +1349,78 @@ mod tests {
         let out = ParameterSimplificationPass::default().run(&c, &mut new_props());
         assert_eq!(out.operations.len(), 0);
     }
+
+    #[test]
+    fn test_parameter_simplification_drops_identity_u() {
+        use std::f64::consts::FRAC_PI_2;
+        let mut c = Circuit::new(1, 0);
+        for params in [
+            vec![0.0, 0.0, 0.0],
+            vec![0.1, 0.0, 0.0],
+            vec![0.0, FRAC_PI_2, -FRAC_PI_2],
+            vec![0.0, FRAC_PI_2, 0.0],
+        ] {
+            c.add_op(Operation::Gate {
+                name: GateType::U,
+                qubits: vec![0],
+                params,
+            });
+        }
+        let out = ParameterSimplificationPass::default().run(&c, &mut new_props());
+        assert_eq!(out.operations.len(), 2, "{:?}", out.operations);
+        assert!(matches!(
+            &out.operations[0],
+            Operation::Gate { params, .. } if (params[0] - 0.1).abs() < 1e-12
+        ));
+        assert!(matches!(
+            &out.operations[1],
+            Operation::Gate { params, .. } if (params[1] - FRAC_PI_2).abs() < 1e-12
+        ));
+    }
+
+    #[test]
+    fn test_parameter_simplification_tracks_sign_flips() {
+        use crate::simulator::circuit_to_unitary;
+        use num_complex::Complex;
+        use std::f64::consts::PI;
+
+        let gate = |name: GateType, params: Vec<f64>| Operation::Gate {
+            name,
+            qubits: vec![0],
+            params,
+        };
+        for (ops, kept, phase) in [
+            (vec![gate(GateType::U, vec![2.0 * PI, 0.0, 0.0])], 0, PI),
+            (vec![gate(GateType::U, vec![4.0 * PI, 0.0, 0.0])], 0, 0.0),
+            (vec![gate(GateType::RX, vec![-2.0 * PI])], 0, PI),
+            (
+                vec![
+                    gate(GateType::RX, vec![3.0 * PI]),
+                    gate(GateType::U, vec![0.3 - 2.0 * PI, 5.0 * PI, -0.2]),
+                    gate(GateType::RY, vec![0.4 + 2.0 * PI]),
+                    gate(GateType::RZ, vec![-0.5 - 2.0 * PI]),
+                ],
+                4,
+                PI,
+            ),
+        ] {
+            let mut c = Circuit::new(1, 0);
+            for op in ops {
+                c.add_op(op);
+            }
+            let out = ParameterSimplificationPass::default().run(&c, &mut new_props());
+            assert_eq!(out.operations.len(), kept, "{:?}", c.operations);
+            assert!(
+                (out.global_phase - phase).abs() < 1e-12,
+                "{:?}",
+                c.operations
+            );
+            let exact = circuit_to_unitary(&out) * Complex::from_polar(1.0, out.global_phase);
+            assert!(
+                (exact - circuit_to_unitary(&c)).norm() < 1e-9,
+                "{:?}",
+                c.operations
+            );
+        }
+    }
 }
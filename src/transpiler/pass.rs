@@ -13,6 +13,7 @@
 //!   pass-prerequisite design.
 
 use super::property_set::PropertySet;
+use crate::error::{QRustError, Result};
 use crate::ir::Circuit;
 
 /// A transpiler pass: consumes a circuit, produces a (possibly) transformed one.
@@ -132,6 +133,54 @@ impl PassManager {
         current
     }
 
+    /// Runs the pipeline on the operations of region `region_name` only
+    /// (see [`Circuit::region`]) and splices the result back in place,
+    /// leaving every other operation untouched. Other regions keep their
+    /// operations: ranges after the region shift by the change in length,
+    /// and those nested in it are clamped to its new end.
+    ///
+    /// The pipeline must not change the circuit's width, so layout and
+    /// routing passes do not belong here.
+    ///
+    /// # Errors
+    /// Returns [`QRustError::Undefined`] if the circuit has no such region,
+    /// or [`QRustError::InvalidConfig`] if a pass changed the qubit or
+    /// classical-bit count.
+    pub fn run_on_region(&mut self, circuit: &Circuit, region_name: &str) -> Result<Circuit> {
+        let range = circuit
+            .regions
+            .get(region_name)
+            .cloned()
+            .ok_or_else(|| QRustError::Undefined(format!("region '{}'", region_name)))?;
+        let sub = circuit
+            .region(region_name)
+            .ok_or_else(|| QRustError::Undefined(format!("region '{}'", region_name)))?;
+        let done = self.run(&sub);
+        if (done.num_qubits, done.num_cbits) != (sub.num_qubits, sub.num_cbits) {
+            return Err(QRustError::InvalidConfig(format!(
+                "pipeline changed region '{}' from {} qubit(s)/{} bit(s) to {}/{}",
+                region_name, sub.num_qubits, sub.num_cbits, done.num_qubits, done.num_cbits
+            )));
+        }
+
+        let new_end = range.start + done.operations.len();
+        let remap = |p: usize| {
+            if p <= range.start {
+                p
+            } else if p >= range.end {
+                p - range.end + new_end
+            } else {
+                p.min(new_end)
+            }
+        };
+        let mut out = circuit.clone();
+        out.operations.splice(range.clone(), done.operations);
+        for r in out.regions.values_mut() {
+            *r = remap(r.start)..remap(r.end);
+        }
+        Ok(out)
+    }
+
     /// Returns the number of registered entries (conditional + unconditional).
     ///
     /// # Example
@@ -143,6 +192,44 @@ impl PassManager {
     pub fn num_passes(&self) -> usize {
         self.entries.len()
     }
+
+    /// Returns the [`Pass::name`] of every entry, in execution order.
+    /// Conditional entries are included whether or not they would run.
+    pub fn pass_names(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .map(|entry| match entry {
+                PassEntry::Always(p) => p.name(),
+                PassEntry::Conditional { pass, .. } => pass.name(),
+            })
+            .collect()
+    }
+
+    /// Owned form of [`PassManager::pass_names`], for logging or storing
+    /// the exact pipeline alongside results.
+    pub fn describe(&self) -> Vec<String> {
+        self.pass_names().into_iter().map(String::from).collect()
+    }
+}
+
+/// Renders the pipeline as `A -> B -> C`, marking predicate-guarded entries
+/// with `(conditional)`. An empty manager renders as `<empty>`.
+impl std::fmt::Display for PassManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.entries.is_empty() {
+            return f.write_str("<empty>");
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" -> ")?;
+            }
+            match entry {
+                PassEntry::Always(p) => f.write_str(p.name())?,
+                PassEntry::Conditional { pass, .. } => write!(f, "{} (conditional)", pass.name())?,
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -239,6 +326,50 @@ mod tests {
         assert_eq!(pm.num_passes(), 2);
     }
 
+    #[test]
+    fn test_describe_lists_passes_in_order() {
+        let mut pm = PassManager::new();
+        assert!(pm.describe().is_empty());
+        assert_eq!(pm.to_string(), "<empty>");
+        pm.add_pass(Box::new(MockPass));
+        pm.add_conditional(Box::new(FlagPass { key: "x" }), |_| true);
+        pm.add_pass(Box::new(MockPass));
+        assert_eq!(pm.describe(), vec!["MockPass", "FlagPass", "MockPass"]);
+        assert_eq!(
+            pm.to_string(),
+            "MockPass -> FlagPass (conditional) -> MockPass"
+        );
+    }
+
+    #[test]
+    fn test_run_on_region_leaves_rest_untouched() {
+        use crate::transpiler::optimization::InverseCancellationPass;
+        let c = crate::parser::parse_qasm(
+            "OPENQASM 2.0;\nqreg q[2];\nh q[0];\nh q[0];\n\
+             // @region-begin hot\nx q[1];\ncx q[0], q[1];\ncx q[0], q[1];\n\
+             // @region-end hot\n// @region-begin tail\nz q[1];\nz q[1];\n\
+             // @region-end tail\n",
+        )
+        .unwrap();
+        let mut pm = PassManager::new();
+        pm.add_pass(Box::new(InverseCancellationPass));
+
+        let out = pm.run_on_region(&c, "hot").unwrap();
+        let names: Vec<String> = out.operations.iter().map(|op| op.to_qasm()).collect();
+        assert_eq!(
+            names,
+            ["h q[0];", "h q[0];", "x q[1];", "z q[1];", "z q[1];"]
+        );
+        assert_eq!(out.regions["hot"], 2..3);
+        assert_eq!(out.regions["tail"], 3..5);
+        assert_eq!(out.region("tail").unwrap().operations, c.operations[5..7]);
+
+        assert!(matches!(
+            pm.run_on_region(&c, "cold"),
+            Err(QRustError::Undefined(_))
+        ));
+    }
+
     /// Verifies that `CircuitProfilerPass` (an analysis pass) does not mutate
     /// the circuit's observable state.
     #[test]
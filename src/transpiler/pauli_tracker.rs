@@ -27,8 +27,7 @@ impl Pass for PauliTrackerPass {
         _property_set: &mut crate::transpiler::property_set::PropertySet,
     ) -> Circuit {
         let mut frames: HashMap<usize, (bool, bool)> = HashMap::new();
-        let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-        out.custom_gates = circuit.custom_gates.clone();
+        let mut out = circuit.empty_like();
 
         fn flush(q: usize, out: &mut Circuit, f: &mut HashMap<usize, (bool, bool)>) {
             if let Some((x, z)) = f.remove(&q) {
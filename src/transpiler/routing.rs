@@ -13,6 +13,12 @@
 //!   rather than absolute scores per the paper's Eq. 5.
 //!
 //! Default is `Static(0.5)` for backward compatibility with existing tests.
+//!
+//! ## Error-aware routing
+//!
+//! Distances in the score and SWAP costs use [`Backend::edge_weight`]. With
+//! no recorded edge errors these are hop counts; otherwise routing prefers
+//! paths with the lowest cumulative two-qubit error.
 
 use crate::backend::Backend;
 use crate::error::{QRustError, Result};
@@ -216,10 +222,10 @@ impl Beam {
         self.stuck_swaps = 0;
     }
 
-    fn apply_swap(&mut self, p1: usize, p2: usize) {
+    fn apply_swap(&mut self, p1: usize, p2: usize, cost: f64) {
         self.layout.swap_physical(p1, p2);
         self.actions.push(RoutingAction::InsertSwap(p1, p2));
-        self.cumulative_cost += SWAP_COST;
+        self.cumulative_cost += cost;
         self.stuck_swaps += 1;
     }
 }
@@ -227,12 +233,12 @@ impl Beam {
 const SWAP_COST: f64 = 3.0;
 
 #[inline]
-fn safe_dist(dist: &[Vec<usize>], a: usize, b: usize) -> f64 {
+fn safe_dist(dist: &[Vec<f64>], a: usize, b: usize) -> f64 {
     let d = dist[a][b];
-    if d == usize::MAX {
-        1e9
+    if d.is_finite() {
+        d
     } else {
-        d as f64
+        1e9
     }
 }
 
@@ -241,7 +247,7 @@ fn relative_score(
     swap: (usize, usize),
     front: &[usize],
     gates: &[TwoQGate],
-    dist: &[Vec<usize>],
+    dist: &[Vec<f64>],
     strategy: LookaheadStrategy,
 ) -> f64 {
     let (pa, pb) = swap;
@@ -312,6 +318,12 @@ fn relative_score(
     }
 }
 
+/// Cost of a SWAP across `swap`: three two-qubit gates on that edge.
+#[inline]
+fn swap_cost(backend: &Backend, swap: (usize, usize)) -> f64 {
+    SWAP_COST * backend.edge_weight(swap.0, swap.1)
+}
+
 #[inline]
 fn cmp_f64(a: f64, b: f64) -> Ordering {
     a.partial_cmp(&b).unwrap_or(Ordering::Equal)
@@ -323,6 +335,7 @@ fn beam_sabre_forward(
     preds: &[u16],
     backend: &Backend,
     dist: &[Vec<usize>],
+    cost: &[Vec<f64>],
     initial_layout: &Layout,
     beam_width: usize,
     branch_factor: usize,
@@ -372,10 +385,13 @@ fn beam_sabre_forward(
                         swap,
                         &beam.front_layer,
                         gates,
-                        dist,
+                        cost,
                         strategy,
                     );
-                    (beam.cumulative_cost + SWAP_COST + delta, swap)
+                    (
+                        beam.cumulative_cost + swap_cost(backend, swap) + delta,
+                        swap,
+                    )
                 })
                 .collect();
             scored.sort_by(|a, b| cmp_f64(a.0, b.0));
@@ -388,7 +404,7 @@ fn beam_sabre_forward(
                 scored[0].1,
                 &beam.front_layer,
                 gates,
-                dist,
+                cost,
                 strategy,
             );
             let effective_branch = if best_delta >= 0.0 {
@@ -399,7 +415,7 @@ fn beam_sabre_forward(
             for i in 0..effective_branch {
                 let mut child = beam.clone();
                 let (_, swap) = scored[i];
-                child.apply_swap(swap.0, swap.1);
+                child.apply_swap(swap.0, swap.1, swap_cost(backend, swap));
                 if child.stuck_swaps < 3 * backend.num_qubits {
                     new_beams.push(child);
                 }
@@ -458,8 +474,11 @@ fn reconstruct_circuit(
     num_physical: usize,
     single_q_ops: &[Vec<(usize, Operation)>],
 ) -> Circuit {
-    let mut output = Circuit::new(num_physical, original.num_cbits);
-    output.custom_gates = original.custom_gates.clone();
+    let mut output = original.empty_like();
+    // Physical wires replace the logical ones, so quantum registers no
+    // longer apply; classical registers and the phase carry over.
+    output.num_qubits = num_physical;
+    output.qregs.clear();
     let mut layout = initial_layout.clone();
     let mut sq_cursors: Vec<usize> = vec![0; original.num_qubits];
 
@@ -563,6 +582,20 @@ fn emit_non_gate_op(op: &Operation, layout: &Layout, output: &mut Circuit) {
 
 // ─── BeamSabrePass ─────────────────────────────────────────────────────────
 
+/// Outcome of [`BeamSabrePass::route`]: the routed circuit plus the routing
+/// metrics that [`BeamSabrePass::try_run`] records in the property set.
+#[derive(Debug, Clone)]
+pub struct RoutingResult {
+    /// Routed circuit, indexed by physical qubit.
+    pub circuit: Circuit,
+    /// Number of SWAP gates inserted by routing.
+    pub added_swaps: usize,
+    /// Logical-to-physical mapping at the start of the circuit.
+    pub initial_layout: Vec<usize>,
+    /// Logical-to-physical mapping at the end of the circuit.
+    pub final_layout: Vec<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub struct BeamSabrePass {
     pub backend: Backend,
@@ -587,6 +620,30 @@ impl Default for BeamSabrePass {
 }
 
 impl BeamSabrePass {
+    /// Routes `circuit` with a fresh property set and returns the result
+    /// together with its SWAP count and layouts. Circuits without 2-qubit
+    /// gates are returned unchanged with trivial layouts.
+    pub fn route(&self, circuit: &Circuit) -> Result<RoutingResult> {
+        let mut property_set = PropertySet::new();
+        let routed = self.try_run(circuit, &mut property_set)?;
+        let trivial = || Layout::trivial(circuit.num_qubits, self.backend.num_qubits);
+        Ok(RoutingResult {
+            circuit: routed,
+            added_swaps: property_set
+                .get::<usize>("swaps_inserted")
+                .copied()
+                .unwrap_or(0),
+            initial_layout: property_set
+                .get::<Vec<usize>>("initial_layout")
+                .cloned()
+                .unwrap_or_else(|| trivial().logical_to_physical),
+            final_layout: property_set
+                .get::<Vec<usize>>("final_layout")
+                .cloned()
+                .unwrap_or_else(|| trivial().logical_to_physical),
+        })
+    }
+
     pub fn try_run(&self, circuit: &Circuit, property_set: &mut PropertySet) -> Result<Circuit> {
         let has_2q = circuit.operations.iter().any(|op| {
             if let Operation::Gate { qubits, .. } = op {
@@ -621,6 +678,7 @@ impl BeamSabrePass {
         }
 
         let dist = self.backend.shortest_path_matrix();
+        let cost = self.backend.weighted_distance_matrix();
         let (gates, preds, single_q_ops) = build_gate_graph(circuit);
 
         let mut best_layout =
@@ -676,6 +734,7 @@ impl BeamSabrePass {
                 &preds,
                 &self.backend,
                 &dist,
+                &cost,
                 &current_initial,
                 self.beam_width,
                 self.branch_factor,
@@ -700,6 +759,7 @@ impl BeamSabrePass {
                     &reversed_preds,
                     &self.backend,
                     &dist,
+                    &cost,
                     &fwd_best.layout,
                     self.beam_width,
                     self.branch_factor,
@@ -727,7 +787,12 @@ impl BeamSabrePass {
             best_initial_layout.logical_to_physical.clone(),
         );
         property_set.insert("final_layout", winning.layout.logical_to_physical.clone());
-        property_set.insert("swaps_inserted", winning.cumulative_cost as usize / 3);
+        let swaps_inserted = winning
+            .actions
+            .iter()
+            .filter(|a| matches!(a, RoutingAction::InsertSwap(..)))
+            .count();
+        property_set.insert("swaps_inserted", swaps_inserted);
 
         Ok(reconstruct_circuit(
             circuit,
@@ -775,6 +840,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_route_reports_swap_count_on_linear() {
+        let mut c = Circuit::new(3, 0);
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 2],
+            params: vec![],
+        });
+        let result = default_pass(Backend::linear(3), 1, 1, 1).route(&c).unwrap();
+        assert_eq!(result.added_swaps, 1);
+        let swaps = result
+            .circuit
+            .operations
+            .iter()
+            .filter(|op| {
+                matches!(
+                    op,
+                    Operation::Gate {
+                        name: GateType::SWAP,
+                        ..
+                    }
+                )
+            })
+            .count();
+        assert_eq!(swaps, result.added_swaps);
+        assert_eq!(result.initial_layout, vec![0, 1, 2]);
+        assert_ne!(result.final_layout, result.initial_layout);
+    }
+
+    #[test]
+    fn test_route_prefers_low_error_path() {
+        // 0 - 1 - 2 is short but noisy; 0 - 3 - 4 - 2 is longer but clean.
+        let mut backend = Backend::new("weighted", 5);
+        backend.set_coupling_map(
+            [(0, 1), (1, 2), (0, 3), (3, 4), (4, 2)]
+                .into_iter()
+                .flat_map(|(a, b)| [(a, b), (b, a)]),
+        );
+        for (a, b, e) in [
+            (0, 1, 0.2),
+            (1, 2, 0.2),
+            (0, 3, 1e-3),
+            (3, 4, 1e-3),
+            (4, 2, 1e-3),
+        ] {
+            backend.set_edge_error(a, b, e).unwrap();
+        }
+        let mut c = Circuit::new(3, 0);
+        c.add_op(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 2],
+            params: vec![],
+        });
+
+        let result = default_pass(backend, 4, 2, 1).route(&c).unwrap();
+        assert_eq!(result.added_swaps, 2);
+        for op in &result.circuit.operations {
+            if let Operation::Gate { qubits, .. } = op {
+                assert!(!qubits.contains(&1), "noisy qubit used: {op:?}");
+            }
+        }
+    }
+
     #[test]
     fn test_layout_trivial() {
         let l = Layout::trivial(3, 5);
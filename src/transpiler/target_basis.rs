@@ -319,8 +319,7 @@ impl Pass for TargetBasisPass {
         let lower: HashSet<String> = self.basis.iter().map(|s| s.to_lowercase()).collect();
         let lib = build_equivalence_library(&self.basis);
 
-        let mut out = Circuit::new(circuit.num_qubits, circuit.num_cbits);
-        out.custom_gates = circuit.custom_gates.clone();
+        let mut out = circuit.empty_like();
 
         for op in &circuit.operations {
             match op {
@@ -20,8 +20,8 @@
 use crate::error::{QRustError, Result};
 use crate::ir::Circuit;
 use crate::simulator::{
-    circuit_to_unitary, equivalence_by_sampling, unitary_fidelity, MAX_QUBITS,
-    MAX_STATE_VECTOR_QUBITS,
+    circuit_to_unitary, equivalence_by_sampling, try_circuit_to_unitary, unitary_fidelity,
+    MAX_QUBITS, MAX_STATE_VECTOR_QUBITS,
 };
 
 /// Threshold above which the exact unitary check is too memory-intensive.
@@ -146,6 +146,76 @@ pub fn verify_equivalence_with(
     })
 }
 
+/// Checks that `actual` implements the same unitary as `expected` up to
+/// global phase, reporting where they diverge. Backs
+/// [`Circuit::verify_against`].
+///
+/// Up to [`EXACT_VERIFY_LIMIT`] qubits, both unitaries are built, `actual`
+/// is aligned to `expected`'s global phase at `expected`'s largest entry,
+/// and every entry must then agree within `tol`. Larger circuits fall back
+/// to [`verify_equivalence_with`] with `tol` as the fidelity tolerance.
+///
+/// # Errors
+/// - [`QRustError::NotEquivalent`] describing the largest amplitude
+///   difference (or the sampled fidelity) when the circuits differ.
+/// - [`QRustError::SizeMismatch`] if the qubit counts differ.
+/// - Any simulation error, e.g. for an undefined custom gate.
+pub fn verify_against(expected: &Circuit, actual: &Circuit, tol: f64) -> Result<()> {
+    if expected.num_qubits != actual.num_qubits {
+        return Err(QRustError::SizeMismatch(format!(
+            "cannot compare a {}-qubit circuit against a {}-qubit one",
+            expected.num_qubits, actual.num_qubits
+        )));
+    }
+    if expected.num_qubits > EXACT_VERIFY_LIMIT {
+        let verdict = verify_equivalence_with(
+            expected,
+            actual,
+            DEFAULT_SAMPLE_COUNT,
+            0x00C0_FFEE_DEAD_BEEF_u64,
+            tol,
+            tol,
+        )?;
+        return if verdict.is_equivalent() {
+            Ok(())
+        } else {
+            Err(QRustError::NotEquivalent(verdict.describe()))
+        };
+    }
+
+    let u1 = try_circuit_to_unitary(expected)?;
+    let u2 = try_circuit_to_unitary(actual)?;
+    let pivot = (0..u1.nrows())
+        .flat_map(|r| (0..u1.ncols()).map(move |c| (r, c)))
+        .max_by(|&a, &b| u1[a].norm().total_cmp(&u1[b].norm()))
+        .unwrap_or((0, 0));
+    let phase = if u1[pivot].norm() > 0.0 && u2[pivot].norm() > 0.0 {
+        let ratio = u2[pivot] / u1[pivot];
+        ratio / ratio.norm()
+    } else {
+        num_complex::Complex::new(1.0, 0.0)
+    };
+    let mut worst = (0.0_f64, 0, 0);
+    for r in 0..u1.nrows() {
+        for c in 0..u1.ncols() {
+            let diff = (u2[(r, c)] - phase * u1[(r, c)]).norm();
+            if diff > worst.0 {
+                worst = (diff, r, c);
+            }
+        }
+    }
+    let (diff, row, col) = worst;
+    if diff <= tol {
+        return Ok(());
+    }
+    let n = expected.num_qubits;
+    Err(QRustError::NotEquivalent(format!(
+        "max amplitude difference {diff:.6} at <{row:0n$b}|U|{col:0n$b}> \
+         (fidelity {:.6}, tolerance {tol:e})",
+        unitary_fidelity(&u1, &u2)
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,6 +290,33 @@ mod tests {
         assert!(verify_equivalence(&c1, &c2).is_err());
     }
 
+    #[test]
+    fn test_verify_against_equivalent_decomposition() {
+        let decomposed =
+            crate::transpiler::decomposition::try_decompose_basis(&bell_pair()).unwrap();
+        assert!(bell_pair().verify_against(&decomposed, 1e-9).is_ok());
+    }
+
+    #[test]
+    fn test_verify_against_reports_divergence() {
+        let mut broken = bell_pair();
+        broken.add_op(Operation::Gate {
+            name: GateType::RZ,
+            qubits: vec![1],
+            params: vec![0.6],
+        });
+        let err = bell_pair().verify_against(&broken, 1e-9).unwrap_err();
+        assert!(matches!(err, QRustError::NotEquivalent(_)));
+        let msg = err.to_string();
+        assert!(msg.contains("max amplitude difference"), "{msg}");
+        assert!(msg.contains("fidelity"), "{msg}");
+
+        assert!(matches!(
+            bell_pair().verify_against(&Circuit::new(3, 0), 1e-9),
+            Err(QRustError::SizeMismatch(_))
+        ));
+    }
+
     #[test]
     fn test_verdict_describe_formats() {
         let v = Verdict::ExactlyEquivalent { fidelity: 1.0 };
@@ -96,4 +96,29 @@ my_rotation(1.57) q[0];
         }
         assert_eq!(d.operations.len(), 6);
     }
+
+    #[test]
+    fn test_sampler_respects_non_contiguous_cbits() {
+        let qasm = r#"
+            OPENQASM 2.0;
+            include "qelib1.inc";
+            qreg q[2];
+            creg c[3];
+            x q[0];
+            measure q[0] -> c[2];
+            measure q[1] -> c[0];
+        "#;
+        let circuit = parse_qasm(qasm).expect("parse");
+        assert_eq!(
+            circuit.operations[1..],
+            [
+                Operation::Measure { qubit: 0, cbit: 2 },
+                Operation::Measure { qubit: 1, cbit: 0 },
+            ]
+        );
+        // Bitstrings read c[2] c[1] c[0].
+        let counts = q_rust::simulator::sample(&circuit, 16, 7).expect("sample");
+        assert_eq!(counts.len(), 1, "{counts:?}");
+        assert_eq!(counts.get("100"), Some(&16));
+    }
 }
@@ -0,0 +1,37 @@
+//! Builds the core IR the way a `no_std + alloc` consumer would. CI runs this
+//! target with `--no-default-features`, so it must stay free of `std` paths.
+
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use q_rust::ir::{Circuit, GateType, Operation};
+
+    #[test]
+    fn test_build_circuit_and_count_ops() {
+        let mut c = Circuit::new(2, 2);
+        c.append(Operation::Gate {
+            name: GateType::H,
+            qubits: vec![0],
+            params: vec![],
+        })
+        .unwrap();
+        c.append(Operation::Gate {
+            name: GateType::CX,
+            qubits: vec![0, 1],
+            params: vec![],
+        })
+        .unwrap();
+        c.measure_all();
+
+        assert_eq!(c.len(), 4);
+        assert_eq!(c.gate_count(), 2);
+        assert_eq!(c.num_two_qubit_gates(), 1);
+        assert_eq!(c.count_ops()[&GateType::CX], 1);
+        assert_eq!(c.depth(), 3);
+        assert!(c.append(Operation::Measure { qubit: 2, cbit: 0 }).is_err());
+    }
+}
@@ -30,6 +30,22 @@ fn test_teleportation_circuit() {
     assert_eq!(circuit.operations.len(), 9);
 }
 
+#[test]
+fn test_multiple_statements_on_one_line() {
+    let circuit = parse_qasm("OPENQASM 2.0; qreg q[2]; creg c[2];").expect("parse");
+    assert_eq!(circuit.num_qubits, 2);
+    assert_eq!(circuit.num_cbits, 2);
+
+    let qasm = r#"
+        OPENQASM 2.0;
+        qreg q[2];creg c[2]; h q[0];cx q[0], q[1];
+        measure q -> c;
+    "#;
+    let circuit = parse_qasm(qasm).expect("parse");
+    assert_eq!(circuit.num_cbits, 2);
+    assert_eq!(circuit.operations.len(), 4);
+}
+
 #[test]
 fn test_whitespace_tolerance() {
     let qasm = r#"
@@ -82,6 +98,60 @@ fn test_custom_include_error() {
     assert!(format!("{err}").contains("Includes are not supported"));
 }
 
+#[test]
+fn test_includes_resolved_from_search_paths() {
+    use q_rust::parser::parse_qasm_with_includes;
+    use std::fs;
+
+    let root = std::env::temp_dir().join(format!("qrust_includes_{}", std::process::id()));
+    let (lib, extra) = (root.join("lib"), root.join("extra"));
+    fs::create_dir_all(&lib).unwrap();
+    fs::create_dir_all(&extra).unwrap();
+    fs::write(
+        lib.join("bell.inc"),
+        "// shared helpers\ninclude \"regs.inc\";\ngate bell a, b\n{\n  h a;\n  cx a, b;\n}\n",
+    )
+    .unwrap();
+    fs::write(extra.join("regs.inc"), "qreg q[2];\ncreg c[2];\n").unwrap();
+    fs::write(lib.join("loop_a.inc"), "include \"loop_b.inc\";\n").unwrap();
+    fs::write(lib.join("loop_b.inc"), "include \"loop_a.inc\";\n").unwrap();
+
+    let paths = [lib.clone(), extra.clone()];
+    let src = "OPENQASM 2.0;\ninclude \"qelib1.inc\";\ninclude \"bell.inc\";\n\
+               bell q[0], q[1];\nmeasure q -> c;\n";
+    let c = parse_qasm_with_includes(src, &paths).expect("parse");
+    assert_eq!((c.num_qubits, c.num_cbits), (2, 2));
+    assert_eq!(c.operations.len(), 3);
+    assert!(c.custom_gates.get("bell").is_some());
+
+    // Without search paths the old behaviour is kept.
+    assert!(parse_qasm(src).is_err());
+
+    let missing = parse_qasm_with_includes("OPENQASM 2.0;\ninclude \"nope.inc\";\n", &paths)
+        .unwrap_err()
+        .to_string();
+    assert!(missing.contains("nope.inc"), "{missing}");
+    assert!(
+        missing.contains(&lib.join("nope.inc").display().to_string()),
+        "{missing}"
+    );
+    assert!(
+        missing.contains(&extra.join("nope.inc").display().to_string()),
+        "{missing}"
+    );
+
+    let cycle = parse_qasm_with_includes("OPENQASM 2.0;\ninclude \"loop_a.inc\";\n", &paths)
+        .unwrap_err()
+        .to_string();
+    assert!(cycle.contains("include cycle"), "{cycle}");
+    assert!(
+        cycle.contains("loop_a.inc -> ") && cycle.contains("loop_b.inc"),
+        "{cycle}"
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
 #[test]
 fn test_qelib1_include_accepted() {
     let qasm = r#"
@@ -95,6 +165,54 @@ fn test_qelib1_include_accepted() {
     assert_eq!(c.operations.len(), 1);
 }
 
+#[test]
+fn test_qelib1_library_gates_resolve() {
+    // As exported by Qiskit's `qasm2.dumps`.
+    let exported = r#"OPENQASM 2.0;
+include "qelib1.inc";
+qreg q[4];
+creg c[4];
+u3(0.3,0.2,0.1) q[0];
+cu1(0.7) q[0],q[1];
+cu3(0.5,0.4,0.3) q[1],q[2];
+p(0.25) q[3];
+cswap q[0],q[1],q[2];
+c3x q[0],q[1],q[2],q[3];
+sx q[2];
+rccx q[1],q[2],q[3];
+measure q -> c;
+"#;
+    let c = parse_qasm(exported).expect("parse");
+    assert_eq!(c.operations.len(), 12);
+    assert!(c.custom_gates.get("cu1").is_some());
+    assert!(c.custom_gates.get("rccx").is_some());
+    // Only the gates actually called (plus their dependencies) are pulled in.
+    assert!(c.custom_gates.get("c4x").is_none());
+
+    let check = |lhs: &str, rhs: &str| {
+        let header = "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[4];\n";
+        let l = parse_qasm(&format!("{header}{lhs}")).expect(lhs);
+        let r = parse_qasm(&format!("{header}{rhs}")).expect(rhs);
+        if let Err(e) = l.verify_against(&r, 1e-9) {
+            panic!("{lhs} vs {rhs}: {e}");
+        }
+    };
+    check("cu1(0.7) q[0],q[1];", "crz(0.7) q[0],q[1];\nu1(0.35) q[0];");
+    check("cp(0.7) q[0],q[1];", "cu1(0.7) q[0],q[1];");
+    check("p(0.4) q[2];", "rz(0.4) q[2];");
+    check(
+        "cswap q[0],q[1],q[2];",
+        "cx q[1],q[2];\nccx q[0],q[2],q[1];\ncx q[1],q[2];",
+    );
+    check("cu3(pi,0,pi) q[0],q[1];", "cx q[0],q[1];");
+    check("sx q[0];\nsx q[0];", "x q[0];");
+    check("rccx q[0],q[1],q[2];\nrccx q[0],q[1],q[2];", "id q[0];");
+    check(
+        "c3x q[0],q[1],q[2],q[3];",
+        "c3sqrtx q[0],q[1],q[2],q[3];\nc3sqrtx q[0],q[1],q[2],q[3];",
+    );
+}
+
 #[test]
 fn test_qasm_round_trip() {
     let qasm = r#"
@@ -115,6 +233,29 @@ fn test_qasm_round_trip() {
     assert_eq!(p1.operations, p2.operations);
 }
 
+#[test]
+fn test_qasm_round_trip_is_exact() {
+    let qasm = r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[3];
+        creg c[3];
+        u3(0.1234567890123456, -pi/3, 1e-7) q[0];
+        rzz(2*pi/7) q[0], q[2];
+        cu1(0.7) q[1], q[2];
+        barrier q[0], q[1];
+        reset q[2];
+        measure q -> c;
+        if(c==5) rx(sqrt(2)) q[1];
+    "#;
+    let p1 = parse_qasm(qasm).expect("parse 1");
+    let gen = p1.to_qasm(None);
+    assert!(gen.contains("barrier q[0], q[1];"), "{gen}");
+    assert!(gen.contains("measure q[2] -> c[2];"), "{gen}");
+    let p2 = parse_qasm(&gen).expect("parse 2");
+    assert_eq!(p1.operations, p2.operations);
+}
+
 #[test]
 fn test_inverse_qft() {
     let qasm = r#"
@@ -147,3 +288,272 @@ fn test_inverse_qft() {
     assert_eq!(c.num_cbits, 4);
     assert_eq!(c.operations.len(), 13);
 }
+
+#[test]
+fn test_register_identifiers() {
+    let qasm = r#"
+        OPENQASM 2.0;
+        qreg _tmp[1];
+        qreg q1[2];
+        creg reg_2[1];
+        cx _tmp[0], q1[1];
+        measure q1[0] -> reg_2[0];
+    "#;
+    let c = parse_qasm(qasm).expect("parse");
+    let names: Vec<&str> = c.qregs.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["_tmp", "q1"]);
+    assert_eq!(c.cregs[0].name, "reg_2");
+    assert_eq!(c.num_qubits, 3);
+    assert_eq!(c.operations.len(), 2);
+}
+
+#[test]
+fn test_register_name_starting_with_digit_rejected() {
+    for decl in ["qreg 1q[2];", "creg 1q[2];"] {
+        let qasm = format!("OPENQASM 2.0;\n{decl}\n");
+        let err = parse_qasm(&qasm).expect_err(decl);
+        assert!(matches!(err, q_rust::QRustError::Syntax(_)), "{err:?}");
+        assert!(err.to_string().contains(decl), "{err}");
+    }
+}
+
+#[test]
+fn test_comments_inside_parameter_and_operand_lists() {
+    let qasm = r#"
+        OPENQASM 2.0;
+        qreg q[2];
+        creg c[2];
+        rx( 0.5 /* angle */ ) q[0];
+        u3(0.1, /* theta */
+           0.2, // phi
+           0.3) q[1];
+        cx q[0], /* target */ q[1];
+        measure q[0] /* readout */ -> c[0];
+        barrier q[0], // both
+                q[1];
+    "#;
+    let circuit = parse_qasm(qasm).expect("parse");
+    assert_eq!(circuit.operations.len(), 5);
+    assert_eq!(circuit.operations[0].to_string(), "rx(0.5) q[0];");
+    match &circuit.operations[1] {
+        q_rust::ir::Operation::Gate { params, .. } => assert_eq!(params, &[0.1, 0.2, 0.3]),
+        other => panic!("expected u3, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_comments_around_measure_arrow() {
+    use q_rust::ir::Operation;
+    let qasm = "OPENQASM 2.0;\nqreg q[2];\ncreg c[2];\n\
+                measure q[0] /* out */ -> c[1];\n\
+                measure q /* all */ -> /* into */ c;\n\
+                measure q[1] // to bit 0\n  -> c[0];\n";
+    let circuit = parse_qasm(qasm).expect("parse");
+    let pairs: Vec<(usize, usize)> = circuit
+        .operations
+        .iter()
+        .map(|op| match op {
+            Operation::Measure { qubit, cbit } => (*qubit, *cbit),
+            other => panic!("expected a measurement, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(pairs, [(0, 1), (0, 0), (1, 1), (1, 0)]);
+}
+
+#[test]
+fn test_duplicate_cbit_write_is_flagged() {
+    let qasm = r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[2];
+        creg c[2];
+        h q[0];
+        cx q[0], q[1];
+        measure q[0] -> c[0];
+        measure q[1] -> c[0];
+    "#;
+    let circuit = parse_qasm(qasm).expect("Failed to parse");
+    let warnings = circuit.validate();
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+    assert!(warnings[0].contains("cbit 0 is written by ops #2 and #3"));
+}
+
+#[test]
+fn test_block_comments() {
+    let qasm = "/* Bell pair\n   benchmark */\nOPENQASM 2.0;\ninclude \"qelib1.inc\";\n\
+                qreg q[2];\n/* entangle\n */ h q[0];\ncx q[0], q[1]; /* done */\n/* trailing */";
+    let circuit = parse_qasm(qasm).expect("Failed to parse");
+    assert_eq!(circuit.operations.len(), 2);
+    assert_eq!(circuit.operations[0].to_qasm(), "h q[0];");
+    assert_eq!(circuit.operations[1].to_qasm(), "cx q[0], q[1];");
+}
+
+#[test]
+fn test_unterminated_block_comment_is_reported() {
+    let qasm = "OPENQASM 2.0;\nqreg q[1];\n/* never closed\nU(0,0,0) q[0];\n";
+    let err = parse_qasm(qasm).unwrap_err();
+    assert!(
+        err.to_string().contains("unterminated block comment"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_syntax_error_reports_line_and_column() {
+    let qasm = "OPENQASM 2.0;\nqreg q[2];\nh q[0];\n  cx q[0] q[1];\n";
+    let err = parse_qasm(qasm).unwrap_err();
+    let q_rust::QRustError::Syntax(located) = &err else {
+        panic!("expected a syntax error, got {err:?}");
+    };
+    assert_eq!((located.line, located.column), (4, 3));
+    assert_eq!(located.snippet, "cx q[0] q[1];");
+    assert_eq!(
+        err.to_string(),
+        "QASM parse error: parse error near: cx q[0] q[1]; (line 4, column 3)"
+    );
+
+    let err = parse_qasm("OPENQASM 2.0;\n/* open\n").unwrap_err();
+    assert!(
+        matches!(&err, q_rust::QRustError::Syntax(e) if (e.line, e.column) == (2, 1)),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn test_unary_minus_in_parameters() {
+    use q_rust::ir::{GateType, Operation};
+    use std::f64::consts::PI;
+    let qasm = r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[1];
+        gate g(a, b) r { rz(-(a+b)) r; }
+        rx(-pi/2) q[0];
+        ry(2 * -3) q[0];
+        rz(-1 - -2) q[0];
+        u1(-pi) q[0];
+        g(0.25, 0.5) q[0];
+    "#;
+    let circuit = parse_qasm(qasm).expect("Failed to parse");
+    let params: Vec<f64> = circuit.operations[..4]
+        .iter()
+        .map(|op| match op {
+            Operation::Gate { params, .. } => params[0],
+            other => panic!("expected a gate, got {other:?}"),
+        })
+        .collect();
+    let expected = [-PI / 2.0, -6.0, 1.0, -PI];
+    for (got, want) in params.iter().zip(expected) {
+        assert!((got - want).abs() < 1e-12, "{params:?}");
+    }
+
+    let unrolled = circuit
+        .decompose_all_custom(&circuit.custom_gates)
+        .expect("unroll");
+    match unrolled.operations.last() {
+        Some(Operation::Gate {
+            name: GateType::RZ,
+            params,
+            ..
+        }) => assert!((params[0] + 0.75).abs() < 1e-12, "{params:?}"),
+        other => panic!("expected rz, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_function_calls_in_parameters() {
+    use q_rust::ir::Operation;
+    let qasm = r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[1];
+        u1(sin(pi/2)) q[0];
+        rx(2 * sqrt(exp(ln(4)))) q[0];
+        ry(cos(0) + tan(0)) q[0];
+    "#;
+    let circuit = parse_qasm(qasm).expect("Failed to parse");
+    let params: Vec<f64> = circuit
+        .operations
+        .iter()
+        .map(|op| match op {
+            Operation::Gate { params, .. } => params[0],
+            other => panic!("expected a gate, got {other:?}"),
+        })
+        .collect();
+    for (got, want) in params.iter().zip([1.0, 4.0, 1.0]) {
+        assert!((got - want).abs() < 1e-12, "{params:?}");
+    }
+
+    let bad = "OPENQASM 2.0;\nqreg q[1];\nU(cosh(0), 0, 0) q[0];\n";
+    let err = parse_qasm(bad).unwrap_err();
+    assert!(err.to_string().contains("function 'cosh'"), "{err}");
+}
+
+#[test]
+fn test_power_operator_precedence() {
+    use q_rust::ir::Operation;
+    let qasm = r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[1];
+        rx(2^3) q[0];
+        rx(2 * 3^2) q[0];
+        rx(1 + 2^2) q[0];
+        rx(2^3^2) q[0];
+        rx(-2^2) q[0];
+        rx(2^-1) q[0];
+        rx((2*3)^2) q[0];
+    "#;
+    let circuit = parse_qasm(qasm).expect("Failed to parse");
+    let params: Vec<f64> = circuit
+        .operations
+        .iter()
+        .map(|op| match op {
+            Operation::Gate { params, .. } => params[0],
+            other => panic!("expected a gate, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(params, vec![8.0, 18.0, 5.0, 512.0, -4.0, 0.5, 36.0]);
+}
+
+#[test]
+fn test_tau_and_euler_constants() {
+    use q_rust::ir::Operation;
+    use std::f64::consts::{E, PI};
+    let qasm = r#"
+        OPENQASM 2.0;
+        include "qelib1.inc";
+        qreg q[1];
+        rz(tau/4) q[0];
+        rz(euler) q[0];
+        rz(ln(e)) q[0];
+    "#;
+    let circuit = parse_qasm(qasm).expect("Failed to parse");
+    let params: Vec<f64> = circuit
+        .operations
+        .iter()
+        .map(|op| match op {
+            Operation::Gate { params, .. } => params[0],
+            other => panic!("expected a gate, got {other:?}"),
+        })
+        .collect();
+    for (got, want) in params.iter().zip([PI / 2.0, E, 1.0]) {
+        assert!((got - want).abs() < 1e-12, "{params:?}");
+    }
+}
+
+#[test]
+fn test_trailing_content_after_last_statement() {
+    let qasm = "OPENQASM 2.0;\nqreg q[1];\nh q[0];\ngarbage\n";
+    let err = parse_qasm(qasm).unwrap_err();
+    let q_rust::QRustError::Syntax(located) = &err else {
+        panic!("expected a syntax error, got {err:?}");
+    };
+    assert_eq!((located.line, located.column), (4, 1));
+    assert_eq!(located.snippet, "garbage");
+
+    let qasm =
+        "OPENQASM 2.0;\nqreg q[1];\nh q[0]; // done\n\n/* trailing\n   block */\n// last line";
+    let circuit = parse_qasm(qasm).expect("trailing comments should be accepted");
+    assert_eq!(circuit.operations.len(), 1);
+}